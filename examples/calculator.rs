@@ -1,6 +1,6 @@
 use eval::eval;
 use guilibrs::widgets::{Button, TextField, TextAlign};
-use guilibrs::{GuiEvent, GUI};
+use guilibrs::{GuiError, GuiEvent, Layout, GUI};
 
 #[derive(Clone, Copy, Default)]
 enum Buttons {
@@ -18,20 +18,19 @@ const BUTTONS: [[&str; 4]; 5] = [
     [".", "0", "c", "="],
 ];
 
-fn main() -> Result<(), String> {
+fn main() -> Result<(), GuiError> {
     let mut calc = setup()?;
     let mut running = true;
     while running {
         match calc.poll() {
             GuiEvent::None => {}
             GuiEvent::Quit => running = false,
-            GuiEvent::ButtonPress(_, button) => match button {
-                Buttons::Num(c) => calc.push_to_textfield(0, c as char),
-                Buttons::Clear => calc.clear_textfield(0),
+            GuiEvent::ButtonPress(_, _, button) => match button {
+                Buttons::Num(c) => calc.push_to_textfield(0, c as char)?,
+                Buttons::Clear => calc.clear_textfield(0)?,
                 Buttons::Equals => {
-                    calc.set_textfield_content(
-                        0, evaluate(calc.textfields().nth(0).unwrap())
-                    )
+                    let result = evaluate(calc.panel("default")?.textfield(0)?);
+                    calc.set_textfield_content(0, result)?
                 }
             },
             _ => {}
@@ -41,7 +40,7 @@ fn main() -> Result<(), String> {
     Ok(())
 }
 
-fn evaluate(textbox: &TextField) -> String {
+fn evaluate(textbox: &TextField<Buttons>) -> String {
     let val = eval(textbox.get_content());
     if val.is_ok() {
         return val.unwrap().to_string();
@@ -50,13 +49,13 @@ fn evaluate(textbox: &TextField) -> String {
     }
 }
 
-fn setup() -> Result<GUI<Buttons>, String> {
+fn setup() -> Result<GUI<Buttons>, GuiError> {
     let mut buttons: Vec<Button<Buttons>> = vec![];
-    for i in 0..4 {
-        for j in 0..5 {
+    for j in 0..5 {
+        for i in 0..4 {
             let button = BUTTONS[j][i];
             buttons.push(
-                Button::new(i as i32 * 90 + 20, 70 + j as i32 * 90 + 20, 60, 60)
+                Button::new(0, 0, 60, 60)
                     .label(button)
                     .color_rgb(
                         50 + i as u8 * 50,
@@ -71,6 +70,7 @@ fn setup() -> Result<GUI<Buttons>, String> {
             )
         }
     }
+    Layout::grid(4).at(20, 90).spacing(30).arrange(&mut buttons);
 
     GUI::new()
         .title("CalculatoRS")
@@ -1,21 +1,25 @@
-use guilibrs::{GUI, GuiEvent, Panel};
+use guilibrs::{GUI, GuiError, GuiEvent, Panel};
 use guilibrs::widgets::{Fader, TextField, Button, TextAlign, DropdownButton};
 
 #[derive(Debug, Clone, Copy, Default)]
-enum Buttons {
+enum Action {
     Login,
     #[default]
     Logout,
+    Red,
+    Green,
+    Blue,
+    Background(u8, u8, u8),
 }
 
-fn main() -> Result<(), String> {
+fn main() -> Result<(), GuiError> {
     let login_screen = Panel::new(
         "login", (20, 20),
         vec![
             Button::new(0, 460, 340, 40)
                 .label("Login")
                 .color_rgb(0, 100, 20)
-                .callback(Buttons::Login)
+                .callback(Action::Login)
         ],
         vec![
             TextField::new(0, 0, 340, 40)
@@ -40,41 +44,50 @@ fn main() -> Result<(), String> {
             Button::new(0, 460, 340, 40)
                 .label("Logout")
                 .color_rgb(120, 20, 20)
-                .callback(Buttons::Logout),
+                .callback(Action::Logout),
         ],
         vec![
             TextField::new(50, 0, 280, 40)
+                .id("rgb")
                 .transparent()
                 .content(&format_rgb(color))
                 .align(TextAlign::Center),
             TextField::new(50, 40, 280, 40)
+                .id("hex")
                 .transparent()
                 .content(&format_hex(color))
                 .align(TextAlign::Center),
         ],
         vec![
             Fader::new(0, 180, 340)
+                .id("red")
+                .callback(Action::Red)
                 .range(0., 255.)
                 .initial(40.),
             Fader::new(0, 240, 340)
+                .id("green")
+                .callback(Action::Green)
                 .range(0., 255.)
                 .initial(40.),
             Fader::new(0, 300, 340)
+                .id("blue")
+                .callback(Action::Blue)
                 .range(0., 255.)
                 .initial(40.)
         ],
         vec![
             DropdownButton::new(40, 350)
+                .id("background")
                 .label("Background")
                 .options(vec![
-                    "Red",
-                    "Blue",
-                    "Green",
+                    ("Red", Action::Background(255, 0, 0)),
+                    ("Blue", Action::Background(0, 0, 255)),
+                    ("Green", Action::Background(0, 255, 0)),
                 ]),
         ]
     );
 
-    let mut gui: GUI<Buttons> = GUI::new()
+    let mut gui: GUI<Action> = GUI::new()
         .panels(&[login_screen, color_editor])
         .initial_panels(&["login"])
         .title("Demo app")
@@ -87,48 +100,48 @@ fn main() -> Result<(), String> {
         match gui.poll() {
             GuiEvent::None => {}
             GuiEvent::Quit => break 'running,
-            GuiEvent::FaderUpdate(panel, u, f) => {
+            GuiEvent::FaderUpdate(panel, u, f, channel) => {
                 println!("Fader {} on panel {} changed to {}", u, panel, f);
-                match (panel, u) {
-                    ("editor", 0) => color.0 = f as u8,
-                    ("editor", 1) => color.1 = f as u8,
-                    ("editor", 2) => color.2 = f as u8,
+                match channel {
+                    Action::Red => color.0 = f as u8,
+                    Action::Green => color.1 = f as u8,
+                    Action::Blue => color.2 = f as u8,
                     _ => {}
                 };
-                gui.panel_set_textfield_content("editor", 0, format_rgb(color));
-                gui.panel_set_textfield_content("editor", 1, format_hex(color));
+                gui.panel_set_textfield_content("editor", "rgb", format_rgb(color))?;
+                gui.panel_set_textfield_content("editor", "hex", format_hex(color))?;
                 gui.set_backround_color(color);
             },
-            GuiEvent::ButtonPress(panel, button) => {
+            GuiEvent::ButtonPress(panel, _, button) => {
                 println!("Button: {:?} clicked on panel {}", button, panel);
                 match button {
-                    Buttons::Login => {
-                        gui.hide_panel("login");
-                        gui.show_panel("editor")
+                    Action::Login => {
+                        gui.hide_panel("login")?;
+                        gui.show_panel("editor")?
                     },
-                    Buttons::Logout => {
-                        gui.hide_panel("editor");
-                        gui.show_panel("login")
+                    Action::Logout => {
+                        gui.hide_panel("editor")?;
+                        gui.show_panel("login")?
                     }
+                    _ => {}
                 }
 
             },
-            GuiEvent::DropdownUpdate(_, idx, option) => {
+            GuiEvent::DropdownUpdate(_, _, option, value) => {
                 println!("DROPDOWN UPDATE: {:?}", option);
-                let color = match (idx, option) {
-                    (0, "Red") => (255, 0, 0),
-                    (0, "Green") => (0, 255, 0),
-                    (0, "Blue") => (0, 0, 255),
+                let color = match value {
+                    Action::Background(r, g, b) => (r, g, b),
                     _ => (0, 0, 0)
                 };
                 println!("color: {:?}", color);
-                gui.panel_set_fader_value("editor", 0, color.0 as f32);
-                gui.panel_set_fader_value("editor", 1, color.1 as f32);
-                gui.panel_set_fader_value("editor", 2, color.2 as f32);
+                gui.panel_set_fader_value("editor", "red", color.0 as f32)?;
+                gui.panel_set_fader_value("editor", "green", color.1 as f32)?;
+                gui.panel_set_fader_value("editor", "blue", color.2 as f32)?;
                 gui.set_backround_color(color);
-                gui.panel_set_textfield_content("editor", 0, format_rgb(color));
-                gui.panel_set_textfield_content("editor", 1, format_hex(color));
+                gui.panel_set_textfield_content("editor", "rgb", format_rgb(color))?;
+                gui.panel_set_textfield_content("editor", "hex", format_hex(color))?;
             }
+            _ => {}
         }
         gui.draw()?;
     }
@@ -23,7 +23,7 @@ fn main() {
 
             // Since there is only one button, there is nothing to identify and we should therefore
             // Ignore the parameters with an underscore
-            GuiEvent::ButtonPress(_, _) => {
+            GuiEvent::ButtonPress(_, _, _) => {
                 // This block executes when the button is pressed
                 println!("Pressed")
             },
@@ -0,0 +1,101 @@
+use guilibrs::widgets::{Interactive, Widget};
+use guilibrs::{rect, Direction, FontManager, FontSource, GuiError, GuiEvent, Panel, Render, GUI};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+#[derive(Debug, Clone, Copy, Default)]
+enum Message {
+    #[default]
+    Nothing,
+    Volume(f32),
+}
+
+// A rotary knob that is turned by dragging up and down
+#[derive(Debug, Clone)]
+struct Knob {
+    rect: Rect,
+    value: f32,
+    is_hovered: bool,
+}
+
+impl Knob {
+    fn new(x: i32, y: i32, size: u32) -> Knob {
+        Knob { rect: rect!(x, y, size, size), value: 0.5, is_hovered: false }
+    }
+}
+
+impl Widget for Knob {
+    fn shift(&mut self, x: i32, y: i32) {
+        self.rect.offset(x, y);
+    }
+    fn bounds(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl Render for Knob {
+    fn render(&self, canvas: &mut Canvas<Window>) -> Result<(), GuiError> {
+        canvas.set_draw_color(match self.is_hovered {
+            true => Color::RGB(110, 110, 110),
+            false => Color::RGB(85, 85, 85),
+        });
+        canvas.fill_rect(self.rect)?;
+        let angle = (0.75 + 1.5 * self.value) * std::f32::consts::PI;
+        let center = self.rect.center();
+        let radius = self.rect.width() as f32 / 2.0;
+        canvas.set_draw_color(Color::RGB(200, 225, 150));
+        canvas.draw_line(
+            center,
+            (
+                center.x + (angle.cos() * radius) as i32,
+                center.y + (angle.sin() * radius) as i32,
+            ),
+        )?;
+        Ok(())
+    }
+
+    fn render_text(&self, _fonts: &FontManager, _canvas: &mut Canvas<Window>, _font: &FontSource) -> Result<(), GuiError> {
+        Ok(())
+    }
+}
+
+impl Interactive<Message> for Knob {
+    fn set_hovered(&mut self, hovered: bool) {
+        self.is_hovered = hovered;
+    }
+    fn drag(&mut self, _x: i32, y: i32) -> Option<Message> {
+        self.value = ((self.rect.bottom() - y) as f32 / self.rect.height() as f32).clamp(0.0, 1.0);
+        Some(Message::Volume(self.value))
+    }
+    fn arrow_key(&mut self, dir: Direction) -> Option<Message> {
+        self.value = match dir {
+            Direction::Up | Direction::Right => (self.value + 0.05).min(1.0),
+            Direction::Down | Direction::Left => (self.value - 0.05).max(0.0),
+        };
+        Some(Message::Volume(self.value))
+    }
+}
+
+fn main() -> Result<(), GuiError> {
+    let panel = Panel::new("mixer", (20, 20), vec![], vec![], vec![], vec![])
+        .custom_widget(Knob::new(0, 0, 80));
+
+    let mut gui: GUI<Message> = GUI::new()
+        .title("Knob")
+        .size(120, 120)
+        .panels(&[panel])
+        .quit_on_escape()
+        .build()?;
+
+    loop {
+        match gui.poll() {
+            GuiEvent::Quit => break,
+            GuiEvent::Custom(_, _, Message::Volume(volume)) => println!("Volume: {:.2}", volume),
+            _ => {}
+        }
+        gui.draw()?;
+    }
+    Ok(())
+}
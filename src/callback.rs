@@ -0,0 +1,122 @@
+use crate::widgets::{WidgetId, WidgetType};
+use crate::{GuiEvent, GUI};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+type ClickFn<T, S> = dyn FnMut(&mut GUI<T, S>, &mut S, T);
+type ChangeFn<T, S> = dyn FnMut(&mut GUI<T, S>, &mut S, f32, T);
+type SelectFn<T, S> = dyn FnMut(&mut GUI<T, S>, &mut S, &str, T);
+type SubmitFn<T, S> = dyn FnMut(&mut GUI<T, S>, &mut S, String, T);
+
+/// Closure registered for a widget with `GUI::on_click`, `on_change`, `on_select` or `on_submit`
+pub(crate) enum Callback<T, S>
+where
+    T: Copy,
+{
+    Click(Box<ClickFn<T, S>>),
+    Change(Box<ChangeFn<T, S>>),
+    Select(Box<SelectFn<T, S>>),
+    Submit(Box<SubmitFn<T, S>>),
+}
+
+// Panel name, widget type and the ID the widget reports in events
+pub(crate) type CallbackKey = (String, WidgetType, WidgetId);
+
+// Shared so a callback keeps running if it replaces or removes itself
+pub(crate) type Callbacks<T, S> = HashMap<CallbackKey, Rc<RefCell<Callback<T, S>>>>;
+
+/// The callback registered for the widget that emitted `event`, if any
+pub(crate) fn callback_for<T, S>(callbacks: &Callbacks<T, S>, event: &GuiEvent<T>) -> Option<Rc<RefCell<Callback<T, S>>>>
+where
+    T: Copy,
+{
+    let (path, w_type, id) = match event {
+        GuiEvent::ButtonPress(path, id, _) => (path, WidgetType::Button, id),
+        GuiEvent::FaderUpdate(path, id, ..) => (path, WidgetType::Fader, id),
+        GuiEvent::DropdownUpdate(path, id, ..) => (path, WidgetType::DropdownButton, id),
+        GuiEvent::TextSubmit(path, id, ..) => (path, WidgetType::TextField, id),
+        _ => return None,
+    };
+    callbacks.get(&(path.name().to_string(), w_type, id.clone())).cloned()
+}
+
+/// Calls `callback` with the argument carried by `event`
+pub(crate) fn invoke<T, S>(callback: &mut Callback<T, S>, gui: &mut GUI<T, S>, state: &mut S, event: &GuiEvent<T>)
+where
+    T: Copy,
+{
+    match (callback, event) {
+        (Callback::Click(f), GuiEvent::ButtonPress(_, _, payload)) => f(gui, state, *payload),
+        (Callback::Change(f), GuiEvent::FaderUpdate(_, _, value, payload)) => f(gui, state, *value, *payload),
+        (Callback::Select(f), GuiEvent::DropdownUpdate(_, _, option, value)) => f(gui, state, option, *value),
+        (Callback::Submit(f), GuiEvent::TextSubmit(_, _, text, payload)) => f(gui, state, text.clone(), *payload),
+        _ => {}
+    }
+}
+
+/// Drops the callback of a removed widget and moves those of later index-addressed widgets of the
+/// same type down one index, so they stay with their widget
+pub(crate) fn widget_removed<T, S>(callbacks: &mut Callbacks<T, S>, panel: &str, w_type: WidgetType, id: WidgetId, idx: usize)
+where
+    T: Copy,
+{
+    *callbacks = callbacks
+        .drain()
+        .filter(|((name, t, i), _)| !(name == panel && *t == w_type && *i == id))
+        .map(|((name, t, i), callback)| match i {
+            WidgetId::Index(i) if name == panel && t == w_type && i > idx => ((name, t, WidgetId::Index(i - 1)), callback),
+            i => ((name, t, i), callback),
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn callbacks(keys: &[(&str, WidgetType, WidgetId)]) -> Callbacks<(), ()> {
+        keys.iter()
+            .map(|(panel, w_type, id)| {
+                let callback = Callback::Change(Box::new(|_, _, _, _| {}));
+                ((panel.to_string(), *w_type, id.clone()), Rc::new(RefCell::new(callback)))
+            })
+            .collect()
+    }
+
+    fn keys(callbacks: &Callbacks<(), ()>) -> Vec<CallbackKey> {
+        let mut keys: Vec<CallbackKey> = callbacks.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn widget_removed_drops_and_shifts_callbacks() {
+        let fader = WidgetType::Fader;
+        let mut registered = callbacks(&[
+            ("main", fader, WidgetId::Index(0)),
+            ("main", fader, WidgetId::Index(1)),
+            ("main", fader, WidgetId::Index(2)),
+            ("main", fader, "red".into()),
+            ("main", WidgetType::Button, WidgetId::Index(2)),
+            ("other", fader, WidgetId::Index(2)),
+        ]);
+        widget_removed(&mut registered, "main", fader, WidgetId::Index(1), 1);
+        assert_eq!(keys(&registered), keys(&callbacks(&[
+            ("main", fader, WidgetId::Index(0)),
+            ("main", fader, WidgetId::Index(1)),
+            ("main", fader, "red".into()),
+            ("main", WidgetType::Button, WidgetId::Index(2)),
+            ("other", fader, WidgetId::Index(2)),
+        ])));
+    }
+
+    #[test]
+    fn removing_a_named_widget_drops_only_its_callback() {
+        let fader = WidgetType::Fader;
+        let mut callbacks = callbacks(&[("main", fader, "red".into()), ("main", fader, "green".into())]);
+        widget_removed(&mut callbacks, "main", fader, "red".into(), 0);
+        assert_eq!(keys(&callbacks), vec![("main".to_string(), fader, WidgetId::from("green"))]);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::error::GuiError;
+use crate::font::{FontManager, FontSource};
+use crate::panel::Panel;
+use crate::rect;
+use crate::widgets::WidgetData;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+const BOUNDS_COLOR: Color = Color::RGB(255, 0, 0);
+const VISUAL_BOUNDS_COLOR: Color = Color::RGB(0, 255, 0);
+const HOVERED_COLOR: Color = Color::RGB(255, 255, 0);
+const PRESSED_COLOR: Color = Color::RGB(255, 0, 255);
+const TEXT_COLOR: Color = Color::RGB(255, 255, 255);
+const TEXT_BACKGROUND: Color = Color::RGBA(0, 0, 0, 180);
+
+/// Draws widget bounds, indices, focus order and hover/press state on top of the GUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebugOverlay {
+    enabled: bool,
+}
+
+impl DebugOverlay {
+    pub const fn new(enabled: bool) -> DebugOverlay {
+        DebugOverlay { enabled }
+    }
+
+    pub const fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn draw<'a, T, I>(
+        &self,
+        canvas: &mut Canvas<Window>,
+        fonts: &FontManager,
+        font: &FontSource,
+        panels: I,
+        hovered: Option<&WidgetData>,
+        pressed: Option<&WidgetData>,
+    ) -> Result<(), GuiError>
+    where
+        T: Copy + Default + 'a,
+        I: Iterator<Item = &'a Panel<T>>,
+    {
+        canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
+
+        for panel in panels.flat_map(|panel| panel.descendants()) {
+            canvas.set_draw_color(BOUNDS_COLOR);
+            canvas.draw_rect(panel.bounds)?;
+
+            for (order, (w_type, idx, bounds, visual_bounds)) in panel.widget_rects().into_iter().enumerate() {
+                // Panel names are unique, so the innermost one identifies the widget
+                let is = |widget: Option<&WidgetData>| widget
+                    .is_some_and(|widget| widget.0.name() == panel.name && widget.1 == w_type && widget.2 == idx);
+                canvas.set_draw_color(VISUAL_BOUNDS_COLOR);
+                canvas.draw_rect(visual_bounds)?;
+                canvas.set_draw_color(BOUNDS_COLOR);
+                canvas.draw_rect(bounds)?;
+                if is(hovered) {
+                    canvas.set_draw_color(HOVERED_COLOR);
+                    canvas.draw_rect(grow(visual_bounds, 2))?;
+                }
+                if is(pressed) {
+                    canvas.set_draw_color(PRESSED_COLOR);
+                    canvas.draw_rect(grow(visual_bounds, 4))?;
+                }
+
+                let texture = fonts.render_text(&format!("#{} {:?}[{}]", order, w_type, idx), font, 10, TEXT_COLOR)?;
+                let (width, height) = fonts.logical_size(&texture);
+                let rect = rect!(visual_bounds.x, visual_bounds.y, width, height);
+                canvas.set_draw_color(TEXT_BACKGROUND);
+                canvas.fill_rect(rect)?;
+                canvas.copy(&texture, None, rect)?;
+            }
+        }
+        canvas.set_blend_mode(sdl2::render::BlendMode::None);
+        Ok(())
+    }
+}
+
+fn grow(rect: Rect, by: i32) -> Rect {
+    rect!(rect.x - by, rect.y - by, rect.w + 2 * by, rect.h + 2 * by)
+}
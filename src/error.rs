@@ -0,0 +1,78 @@
+use crate::widgets::WidgetType;
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GuiError {
+    UnknownPanel(String),
+    /// Panel names must be unique across all panels and their children
+    DuplicatePanel(String),
+    /// The GUI has no panels to fall back on
+    NoPanels,
+    WidgetIndexOutOfRange {
+        panel: String,
+        widget: WidgetType,
+        index: usize,
+    },
+    /// No widget of the type has the ID
+    UnknownWidget {
+        panel: String,
+        widget: WidgetType,
+        id: String,
+    },
+    ValueOutOfRange {
+        value: f32,
+        min: f32,
+        max: f32,
+    },
+    FontLoad(String),
+    Sdl(String),
+}
+
+impl fmt::Display for GuiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GuiError::UnknownPanel(panel) => write!(f, "Panel '{}' doesn't exist", panel),
+            GuiError::DuplicatePanel(panel) => write!(f, "Panel '{}' already exists", panel),
+            GuiError::NoPanels => write!(f, "GUI has no panels"),
+            GuiError::WidgetIndexOutOfRange { panel, widget, index } => {
+                write!(f, "{:?} {} doesn't exist in panel '{}'", widget, index, panel)
+            }
+            GuiError::UnknownWidget { panel, widget, id } => {
+                write!(f, "{:?} '{}' doesn't exist in panel '{}'", widget, id, panel)
+            }
+            GuiError::ValueOutOfRange { value, min, max } => {
+                write!(f, "Value {} is out of range [{}, {}]", value, min, max)
+            }
+            GuiError::FontLoad(e) => write!(f, "Failed to load font: {}", e),
+            GuiError::Sdl(e) => write!(f, "SDL error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GuiError {}
+
+// SDL reports most of its errors as strings
+impl From<String> for GuiError {
+    fn from(e: String) -> GuiError {
+        GuiError::Sdl(e)
+    }
+}
+
+impl From<sdl2::video::WindowBuildError> for GuiError {
+    fn from(e: sdl2::video::WindowBuildError) -> GuiError {
+        GuiError::Sdl(e.to_string())
+    }
+}
+
+impl From<sdl2::IntegerOrSdlError> for GuiError {
+    fn from(e: sdl2::IntegerOrSdlError) -> GuiError {
+        GuiError::Sdl(e.to_string())
+    }
+}
+
+impl From<sdl2::render::TextureValueError> for GuiError {
+    fn from(e: sdl2::render::TextureValueError) -> GuiError {
+        GuiError::Sdl(e.to_string())
+    }
+}
@@ -0,0 +1,203 @@
+use sdl2::pixels::Color;
+use sdl2::render::{Texture, TextureCreator, TextureQuery};
+use sdl2::rwops::RWops;
+use sdl2::ttf::{Font, FontStyle, Sdl2TtfContext};
+use sdl2::video::WindowContext;
+
+use crate::GuiError;
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+const EMBEDDED_FONT: &[u8] = include_bytes!("../Courier_Prime.ttf");
+
+pub type SharedFont = Rc<LoadedFont>;
+
+/// A font that keeps the TTF context it was loaded with alive
+pub struct LoadedFont {
+    // Declared first so the font is closed before the context can be dropped
+    font: Font<'static, 'static>,
+    _ttf: Rc<Sdl2TtfContext>,
+}
+
+impl Deref for LoadedFont {
+    type Target = Font<'static, 'static>;
+
+    fn deref(&self) -> &Font<'static, 'static> {
+        &self.font
+    }
+}
+
+/// A texture that keeps the renderer it was created by alive, so it can outlive the GUI
+pub struct TextTexture {
+    // Declared first so the texture is destroyed before the renderer can be
+    texture: Texture<'static>,
+    _creator: Rc<TextureCreator<WindowContext>>,
+}
+
+impl Deref for TextTexture {
+    type Target = Texture<'static>;
+
+    fn deref(&self) -> &Texture<'static> {
+        &self.texture
+    }
+}
+
+/// Where a font is loaded from
+#[derive(Debug, Clone, Default)]
+pub enum FontSource {
+    /// Courier Prime, compiled into the library
+    #[default]
+    Embedded,
+    File(PathBuf),
+    /// Font data such as `include_bytes!("font.ttf")`
+    Bytes(&'static [u8]),
+}
+
+impl FontSource {
+    fn load<'ttf>(&self, ttf: &'ttf Sdl2TtfContext, size: u16) -> Result<Font<'ttf, 'static>, String> {
+        match self {
+            FontSource::Embedded => ttf.load_font_from_rwops(RWops::from_bytes(EMBEDDED_FONT)?, size),
+            FontSource::File(path) => ttf.load_font(path, size),
+            FontSource::Bytes(bytes) => ttf.load_font_from_rwops(RWops::from_bytes(bytes)?, size),
+        }
+    }
+}
+
+// Font data is compared by address, so that cache lookups don't hash whole files
+impl PartialEq for FontSource {
+    fn eq(&self, other: &FontSource) -> bool {
+        match (self, other) {
+            (FontSource::Embedded, FontSource::Embedded) => true,
+            (FontSource::File(a), FontSource::File(b)) => a == b,
+            (FontSource::Bytes(a), FontSource::Bytes(b)) => std::ptr::eq(*a, *b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for FontSource {}
+
+impl Hash for FontSource {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            FontSource::Embedded => {}
+            FontSource::File(path) => path.hash(state),
+            FontSource::Bytes(bytes) => {
+                bytes.as_ptr().hash(state);
+                bytes.len().hash(state);
+            }
+        }
+    }
+}
+
+impl From<&str> for FontSource {
+    fn from(path: &str) -> FontSource {
+        FontSource::File(PathBuf::from(path))
+    }
+}
+
+impl From<PathBuf> for FontSource {
+    fn from(path: PathBuf) -> FontSource {
+        FontSource::File(path)
+    }
+}
+
+impl From<&'static [u8]> for FontSource {
+    fn from(bytes: &'static [u8]) -> FontSource {
+        FontSource::Bytes(bytes)
+    }
+}
+
+/// Loads each (source, size) pair once and hands out shared handles to it.
+///
+/// Every font and text texture holds a reference-counted handle to the TTF context or texture
+/// creator it came from, so they can be cached by widgets without borrowing the manager.
+/// The renderer and its window are destroyed once the GUI and all cached textures are dropped.
+///
+/// Text is rasterized at the GUI's scale factor so that it stays sharp on a scaled canvas.
+pub struct FontManager {
+    ttf: Rc<Sdl2TtfContext>,
+    textures: Rc<TextureCreator<WindowContext>>,
+    fonts: RefCell<HashMap<FontSource, HashMap<u16, SharedFont>>>,
+    scale: Cell<f32>,
+}
+
+impl FontManager {
+    pub(crate) fn new(ttf: Sdl2TtfContext, textures: TextureCreator<WindowContext>) -> FontManager {
+        FontManager {
+            ttf: Rc::new(ttf),
+            textures: Rc::new(textures),
+            fonts: RefCell::new(HashMap::new()),
+            scale: Cell::new(1.0),
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale.get()
+    }
+
+    pub(crate) fn set_scale(&self, scale: f32) {
+        self.scale.set(scale);
+    }
+
+    /// A font that fails to load is replaced by the embedded font, so that a bad `.font(..)`
+    /// override doesn't stop the GUI from drawing
+    pub fn get(&self, source: &FontSource, size: u16) -> Result<SharedFont, GuiError> {
+        if let Some(font) = self.fonts.borrow().get(source).and_then(|sizes| sizes.get(&size)) {
+            return Ok(Rc::clone(font));
+        }
+        let font = match self.load(source, size) {
+            Err(_) if *source != FontSource::Embedded => self.get(&FontSource::Embedded, size)?,
+            result => result?,
+        };
+        self.fonts
+            .borrow_mut()
+            .entry(source.clone())
+            .or_default()
+            .insert(size, Rc::clone(&font));
+        Ok(font)
+    }
+
+    /// Loads `source` without falling back, so a missing font file is reported
+    pub(crate) fn check(&self, source: &FontSource) -> Result<(), GuiError> {
+        self.load(source, 16).map(drop)
+    }
+
+    fn load(&self, source: &FontSource, size: u16) -> Result<SharedFont, GuiError> {
+        // SAFETY: the context lives behind an Rc that the font holds a clone of, and the
+        // font is dropped before that clone
+        let ttf: &'static Sdl2TtfContext = unsafe { &*Rc::as_ptr(&self.ttf) };
+        let mut font = source.load(ttf, size).map_err(GuiError::FontLoad)?;
+        font.set_style(FontStyle::NORMAL);
+        Ok(Rc::new(LoadedFont { font, _ttf: Rc::clone(&self.ttf) }))
+    }
+
+    /// Rasterizes `text` into a new texture, at `size` multiplied by the scale factor
+    pub fn render_text(&self, text: &str, source: &FontSource, size: u16, color: Color) -> Result<TextTexture, GuiError> {
+        let size = (size as f32 * self.scale()).round().max(1.0) as u16;
+        let surface = self
+            .get(source, size)?
+            .render(text)
+            .blended(color)
+            .map_err(|e| GuiError::Sdl(e.to_string()))?;
+        // SAFETY: as for fonts, the texture holds a clone of the Rc owning its creator
+        let textures: &'static TextureCreator<WindowContext> = unsafe { &*Rc::as_ptr(&self.textures) };
+        Ok(TextTexture {
+            texture: textures.create_texture_from_surface(&surface)?,
+            _creator: Rc::clone(&self.textures),
+        })
+    }
+
+    /// Size to draw a texture from `render_text` at, in unscaled canvas coordinates
+    pub fn logical_size(&self, texture: &Texture) -> (u32, u32) {
+        let TextureQuery { width, height, .. } = texture.query();
+        let scale = self.scale();
+        ((width as f32 / scale).round() as u32, (height as f32 / scale).round() as u32)
+    }
+}
@@ -1,26 +1,53 @@
-use crate::{GuiEvent, BACKROUNDCOLOR, DEBUG};
-use crate::handler::{EventHandler, HandlerEvent};
+use crate::{GuiError, GuiEvent, PanelPath, BACKROUNDCOLOR};
+use crate::callback::{self, Callback, Callbacks};
+use crate::debug::DebugOverlay;
+use crate::font::{FontManager, FontSource};
+use crate::handler::{after_removal, EventHandler, HandlerEvent};
 use crate::panel::Panel;
-use crate::widgets::{Button, Fader, TextField, WidgetData};
+use crate::widgets::{AnyWidget, Button, Fader, TextField, WidgetData, WidgetId, WidgetType};
 
-use sdl2::pixels::Color;
-use sdl2::render::Canvas;
-use sdl2::ttf::Sdl2TtfContext;
+use sdl2::event::Event;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::surface::Surface;
 use sdl2::video::Window;
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
 
-pub struct GUI<T>
+const MIN_SCALE: f32 = 0.5;
+const MAX_SCALE: f32 = 4.0;
+const ZOOM_STEP: f32 = 0.25;
+const MODAL_DIM_COLOR: Color = Color::RGBA(0, 0, 0, 150);
+
+/// `S` is the user state passed to closure callbacks, see `GuiBuilder::build_with_state`
+pub struct GUI<T, S = ()>
 where
     T: Copy,
 {
-    ttf_context: Sdl2TtfContext,
+    fonts: FontManager,
+    font: FontSource,
     canvas: Canvas<Window>,
     backround_color: Color,
     handler: EventHandler,
-    panels: HashMap<&'static str, Panel<T>>,
-    active_panels: Vec<&'static str>,
+    panels: HashMap<Cow<'static, str>, Panel<T>>,
+    panel_order: Vec<Cow<'static, str>>,
+    active_panels: Vec<Cow<'static, str>>,
+    modal: Option<Cow<'static, str>>,
     active_widget: Option<WidgetData>,
+    headless: bool,
+    debug: DebugOverlay,
+    window_size: (u32, u32),
+    scale: f32,
+    base_scale: f32,
+    dirty: bool,
+    // Taken while a callback runs, which gets it as an argument
+    state: Option<S>,
+    callbacks: Callbacks<T, S>,
 }
 impl<T> GUI<T>
 where
@@ -29,98 +56,205 @@ where
     pub fn new() -> GuiBuilder<T> {
         GuiBuilder::new()
     }
+}
 
+impl<T, S> GUI<T, S>
+where
+    T: Copy + Default,
+{
+    /// Blocks until the next event arrives.
     pub fn poll(&mut self) -> GuiEvent<T> {
-        let event = self.handler.poll_blocking(&mut self.panels, &self.active_panels);
-        if event != HandlerEvent::None && unsafe {DEBUG}{
+        let event = self.handler.poll_blocking(&mut self.panels, input_panels(&self.modal, &self.active_panels));
+        self.process(event)
+    }
+
+    /// Returns `GuiEvent::None` immediately if no events are pending.
+    pub fn poll_nonblocking(&mut self) -> GuiEvent<T> {
+        match self.handler.poll_nonblocking(&mut self.panels, input_panels(&self.modal, &self.active_panels)) {
+            Some(event) => self.process(event),
+            None => GuiEvent::None,
+        }
+    }
+
+    /// Waits at most `timeout` for the next event, returning `GuiEvent::None` on timeout.
+    pub fn poll_timeout(&mut self, timeout: Duration) -> GuiEvent<T> {
+        match self.handler.poll_timeout(&mut self.panels, input_panels(&self.modal, &self.active_panels), timeout) {
+            Some(event) => self.process(event),
+            None => GuiEvent::None,
+        }
+    }
+
+    /// Drains all pending events without blocking, skipping `GuiEvent::None`.
+    /// The events are collected first, so the GUI can be changed while handling them.
+    pub fn drain_events(&mut self) -> Vec<GuiEvent<T>> {
+        let mut events = vec![];
+        while let Some(event) = self.handler.poll_nonblocking(&mut self.panels, input_panels(&self.modal, &self.active_panels)) {
+            match self.process(event) {
+                GuiEvent::None => {},
+                event => events.push(event),
+            }
+        }
+        events
+    }
+
+    /// Feeds an SDL event through the same pipeline as `poll`, e.g. for automated tests.
+    pub fn inject(&mut self, event: Event) -> GuiEvent<T> {
+        let event = self.handler.handle(event, &mut self.panels, input_panels(&self.modal, &self.active_panels));
+        self.process(event)
+    }
+
+    fn process(&mut self, event: HandlerEvent) -> GuiEvent<T> {
+        let event = self.apply(event);
+        self.run_callback(&event);
+        event
+    }
+
+    // Runs the callback registered for the widget that emitted `event`
+    fn run_callback(&mut self, event: &GuiEvent<T>) {
+        let Some(callback) = callback::callback_for(&self.callbacks, event) else {
+            return;
+        };
+        // A callback that polls the GUI can't run again until it returns
+        let (Ok(mut callback), Some(mut state)) = (callback.try_borrow_mut(), self.state.take()) else {
+            return;
+        };
+        callback::invoke(&mut callback, self, &mut state, event);
+        self.state = Some(state);
+    }
+
+    fn apply(&mut self, event: HandlerEvent) -> GuiEvent<T> {
+        if event != HandlerEvent::None && self.debug.is_enabled() {
             println!("{:?}", event);
         }
+        // Every event the handler reports changes something on screen
+        if event != HandlerEvent::None {
+            self.dirty = true;
+        }
         match event {
             HandlerEvent::None => return GuiEvent::None,
-            HandlerEvent::Quit { .. } => return GuiEvent::Quit,
+            HandlerEvent::Redraw => return GuiEvent::None,
+            HandlerEvent::Quit => return GuiEvent::Quit,
             HandlerEvent::Escape => self.deselect_all(),
             HandlerEvent::ClickBackround => self.deselect_all(),
+            HandlerEvent::Resize(w, h) => self.resize(w, h),
+            HandlerEvent::PanelPressed(panel) => {
+                if self.panels.get(&panel).is_some_and(|panel| panel.is_draggable()) {
+                    let _ = self.raise_panel(&panel);
+                }
+            },
+            HandlerEvent::MovePanel(panel, x, y) => {
+                if let Some(panel) = self.panels.get_mut(&panel) {
+                    panel.drag_by(x, y);
+                }
+            },
+            HandlerEvent::Scroll(path, x, y) => {
+                if let Some(panel) = self.panel_at_path_mut(&path) {
+                    panel.scroll_by(x, y);
+                }
+            },
+            HandlerEvent::DragScrollbar(path, orientation, position) => {
+                if let Some(panel) = self.panel_at_path_mut(&path) {
+                    panel.drag_scrollbar(orientation, position);
+                }
+            },
+            HandlerEvent::ClosePanel(panel) => {
+                if self.hide_panel(&panel).is_ok() {
+                    return GuiEvent::PanelClose(panel);
+                }
+            },
+            // Zooming only fails if SDL can't resize the window, which leaves the old scale in place
+            HandlerEvent::ZoomIn => {
+                let _ = self.set_scale((self.scale + ZOOM_STEP).min(MAX_SCALE));
+            },
+            HandlerEvent::ZoomOut => {
+                let _ = self.set_scale((self.scale - ZOOM_STEP).max(MIN_SCALE));
+            },
+            HandlerEvent::ZoomReset => {
+                let _ = self.set_scale(self.base_scale);
+            },
             HandlerEvent::ToggleDebug => {
-                unsafe { crate::DEBUG = !crate::DEBUG; }
+                self.toggle_debug();
             },
             HandlerEvent::TextInput(ref text) => {
-                self.panels
-                    .iter_mut()
-                    .for_each(|panel| {
-                        panel.1.push_to_active_textfields(text);
-                    });
+                for panel in input_panels(&self.modal, &self.active_panels) {
+                    if let Some(panel) = self.panels.get_mut(panel) {
+                        panel.push_to_active_textfields(text);
+                    }
+                }
             },
             HandlerEvent::PopChar => {
                 self.pop_active_textfield();
             },
             HandlerEvent::Hover(widget) => {
-                self.hover_widget(widget);
+                if let Some(panel) = self.owner_mut(&widget) {
+                    panel.hover(widget.1, widget.2);
+                }
             },
             HandlerEvent::HoverDropdown(widget, x, y) => {
-                self.panels
-                    .get_mut(widget.0)
-                    .unwrap()
-                    .hover_dropdown(widget.2, x, y);
+                if let Some(panel) = self.owner_mut(&widget) {
+                    panel.hover_dropdown(widget.2, x, y);
+                }
             }
             HandlerEvent::UnHover(widget) => {
-                self.unhover_widget(widget);
+                if let Some(panel) = self.owner_mut(&widget) {
+                    panel.unhover(widget.1, widget.2);
+                }
             },
             HandlerEvent::Drag(widget, x, y) => {
-                if let Some(val) = self.drag(widget, x, y) {
-                    return GuiEvent::FaderUpdate(widget.0, widget.2, val);
+                if let Some(event) = self
+                    .owner_mut(&widget)
+                    .and_then(|panel| panel.drag(&widget, x, y)) {
+                    return event;
                 }
             },
             HandlerEvent::Click(widget) => {
-                if let Some(old_active) = self.active_widget {
+                if let Some(old_active) = self.active_widget.take() {
                     if old_active.0 != widget.0 {
-                        self.panels
-                            .get_mut(old_active.0)
-                            .unwrap()
-                            .deselect(old_active.1, old_active.2);
+                        if let Some(panel) = self.owner_mut(&old_active) {
+                            panel.deselect(old_active.1, old_active.2);
+                        }
                     }
                 }
-                self.active_widget = Some(widget);
-                if let Some(cb) = self.panels
-                    .get_mut(widget.0)
-                    .unwrap()
-                    .click(widget) {
+                self.active_widget = Some(widget.clone());
+                if let Some(cb) = self
+                    .owner_mut(&widget)
+                    .and_then(|panel| panel.click(&widget)) {
                     return cb
                 }
-                
             },
             HandlerEvent::Return => {
-                if let Some(widget) = self.active_widget {
-                    if let Some(cb) = self.panels.get_mut(widget.0).unwrap().click(widget) {
+                if let Some(widget) = self.active_widget.clone() {
+                    if let Some(cb) = self
+                        .owner_mut(&widget)
+                        .and_then(|panel| panel.submit(&widget)) {
                         return cb;
                     }
                 }
             },
             HandlerEvent::Tab => {
-                let panel = match self.active_widget {
-                    Some(w) => w.0,
-                    None => self.panels.keys().nth(0).unwrap()
+                let panel = match &self.active_widget {
+                    Some(w) => Some(w.0.root().to_string()),
+                    None => self.modal
+                        .as_ref()
+                        .or_else(|| self.panel_order.iter().find(|panel| self.active_panels.contains(panel)))
+                        .map(|panel| panel.to_string())
                 };
-                self.active_widget = Some(self.panels
-                    .get_mut(panel)
-                    .unwrap()
-                    .next_widget()
-                )
+                if let Some(panel) = panel.and_then(|panel| self.panels.get_mut(panel.as_str())) {
+                    self.active_widget = panel.next_widget();
+                }
             },
             HandlerEvent::ShitTab => {
-                if let Some(widget) = self.active_widget {
-                    self.active_widget = self.panels
-                        .get_mut(widget.0)
-                        .unwrap()
-                        .previous_widget()
-                    
+                if let Some(widget) = &self.active_widget {
+                    if let Some(panel) = self.panels.get_mut(widget.0.root()) {
+                        self.active_widget = panel.previous_widget();
+                    }
                 }
             },
             HandlerEvent::ArrowKey(dir) => {
-                if let Some(widget) = self.active_widget {
-                    if let Some(event) = self.panels
-                        .get_mut(widget.0)
-                        .unwrap()
-                        .arrow_key(widget.1, widget.2, dir) {
+                if let Some(widget) = self.active_widget.clone() {
+                    if let Some(event) = self
+                        .owner_mut(&widget)
+                        .and_then(|panel| panel.arrow_key(&widget, dir)) {
                             return event
                         }
                 }
@@ -129,148 +263,534 @@ where
         GuiEvent::None
     }
 
-    pub fn draw(&mut self) -> Result<(), String> {
+    /// Redraws the active panels if anything changed since the last draw. Does nothing otherwise.
+    pub fn draw(&mut self) -> Result<(), GuiError> {
+        if !self.needs_redraw() {
+            return Ok(());
+        }
+        self.dirty = false;
         self.canvas.set_draw_color(self.backround_color);
         self.canvas.clear();
-        for panel_name in self.panels.keys() {
-            if self.active_panels.contains(panel_name) {
-                self.panels[panel_name].draw(&mut self.canvas, &self.ttf_context)?;
-            }
+        let background = self.active_panels
+            .iter()
+            .filter(|name| self.modal.as_ref() != Some(*name))
+            .filter_map(|name| self.panels.get(name));
+        for panel in background.clone() {
+            panel.draw(&mut self.canvas, &self.fonts, &self.font)?;
+        }
+        for panel in background {
+            panel.draw_overlay(&mut self.canvas, &self.fonts, &self.font)?;
+        }
+        if let Some(modal) = self.modal.as_ref().and_then(|modal| self.panels.get(modal)) {
+            let viewport = self.canvas.viewport();
+            self.canvas.set_blend_mode(BlendMode::Blend);
+            self.canvas.set_draw_color(MODAL_DIM_COLOR);
+            self.canvas.fill_rect(viewport)?;
+            self.canvas.set_blend_mode(BlendMode::None);
+            modal.draw(&mut self.canvas, &self.fonts, &self.font)?;
+            modal.draw_overlay(&mut self.canvas, &self.fonts, &self.font)?;
+        }
+        if self.debug.is_enabled() {
+            let visible = self.active_panels
+                .iter()
+                .filter_map(|name| self.panels.get(name));
+            self.debug.draw(
+                &mut self.canvas,
+                &self.fonts,
+                &self.font,
+                visible,
+                self.handler.hovered(),
+                self.handler.pressed(),
+            )?;
         }
         self.canvas.present();
         Ok(())
     }
 
-    pub fn textfields(&self) -> std::slice::Iter<'_, TextField> {
-        self.panels.values().nth(0).unwrap().textfields.iter()
+    /// True if a widget, panel or the window changed since the last `draw`.
+    /// Always true while the debug overlay is enabled.
+    pub const fn needs_redraw(&self) -> bool {
+        self.dirty || self.debug.is_enabled()
     }
 
-    pub fn panel_textfields(&self, panel: &'static str) -> std::slice::Iter<'_, TextField> {
-        self.panels[panel].textfields.iter()
+    /// Makes the next `draw` redraw everything
+    pub fn request_redraw(&mut self) {
+        self.dirty = true;
     }
 
-    pub fn set_backround_color(&mut self, rgb: (u8, u8, u8)) {
-        self.backround_color = Color::RGB(rgb.0, rgb.1, rgb.2);
+    /// Enables or disables the debug overlay. F12 toggles it at runtime.
+    pub fn set_debug(&mut self, enabled: bool) {
+        self.debug.set_enabled(enabled);
+        self.dirty = true;
     }
 
-    pub fn panel_set_textfield_content(&mut self, panel: &'static str, idx: usize, content: String) {
-        self.panels
-            .get_mut(panel)
-            .expect(&format!("Panel '{}' doesn't exist", panel))
-            .set_textfield_content(idx, content);
+    pub fn toggle_debug(&mut self) {
+        self.set_debug(!self.debug.is_enabled());
     }
 
-    pub fn set_textfield_content(&mut self, idx: usize, content: String) {
-        self.panels
-            .values_mut()
-            .nth(0)
-            .unwrap()
-            .set_textfield_content(idx, content);
+    pub const fn is_debug(&self) -> bool {
+        self.debug.is_enabled()
+    }
+
+    pub const fn is_headless(&self) -> bool {
+        self.headless
+    }
+
+    /// Copies the last drawn frame into a new surface.
+    pub fn screenshot(&self) -> Result<Surface<'static>, GuiError> {
+        let format = PixelFormatEnum::ARGB8888;
+        let (w, h) = self.canvas.output_size()?;
+        let pixels = self.canvas.read_pixels(None, format)?;
+        let mut surface = Surface::new(w, h, format)?;
+        let src_pitch = w as usize * format.byte_size_per_pixel();
+        let dst_pitch = surface.pitch() as usize;
+        surface.with_lock_mut(|buffer| {
+            for (row, line) in pixels.chunks_exact(src_pitch).enumerate() {
+                buffer[row * dst_pitch..row * dst_pitch + src_pitch].copy_from_slice(line);
+            }
+        });
+        Ok(surface)
     }
 
-    pub fn panel_set_fader_value(&mut self, panel: &'static str, fader: usize, value: f32) {
+    pub fn save_screenshot<P: AsRef<Path>>(&self, path: P) -> Result<(), GuiError> {
+        Ok(self.screenshot()?.save_bmp(path)?)
+    }
+
+    /// A top-level or nested panel
+    pub fn panel(&self, panel: &str) -> Result<&Panel<T>, GuiError> {
         self.panels
-            .get_mut(panel)
-            .expect(&format!("Panel '{}' doesn't exist", panel))
-            .faders.iter_mut().nth(fader)
-            .expect(&format!("Fader {} doesn't exist in panel '{}'", fader, panel))
-            .set_fader_value(value);
+            .values()
+            .find_map(|root| root.find(panel))
+            .ok_or_else(|| GuiError::UnknownPanel(panel.to_string()))
     }
 
-    pub fn set_fader_value(&mut self, fader: usize, value: f32) {
+    /// Marks the GUI for redrawing, since the panel may be changed through the reference
+    pub fn panel_mut(&mut self, panel: &str) -> Result<&mut Panel<T>, GuiError> {
+        self.dirty = true;
         self.panels
             .values_mut()
-            .nth(0)
-            .unwrap()
-            .faders.iter_mut().nth(fader)
-            .expect(&format!("Fader {} doesn't exist", fader))
-            .set_fader_value(value);
+            .find_map(|root| root.find_mut(panel))
+            .ok_or_else(|| GuiError::UnknownPanel(panel.to_string()))
+    }
+
+    pub fn textfields(&self) -> Result<std::slice::Iter<'_, TextField<T>>, GuiError> {
+        Ok(self.default_panel()?.textfields.iter())
+    }
+
+    pub fn panel_textfields(&self, panel: &str) -> Result<std::slice::Iter<'_, TextField<T>>, GuiError> {
+        Ok(self.panel(panel)?.textfields.iter())
+    }
+
+    pub fn set_backround_color(&mut self, rgb: (u8, u8, u8)) {
+        self.backround_color = Color::RGB(rgb.0, rgb.1, rgb.2);
+        self.dirty = true;
+    }
+
+    pub fn panel_set_textfield_content(&mut self, panel: &str, id: impl Into<WidgetId>, content: String) -> Result<(), GuiError> {
+        self.panel_mut(panel)?.set_textfield_content(id, content)
+    }
+
+    pub fn set_textfield_content(&mut self, id: impl Into<WidgetId>, content: String) -> Result<(), GuiError> {
+        self.default_panel_mut()?.set_textfield_content(id, content)
+    }
+
+    pub fn panel_set_fader_value(&mut self, panel: &str, fader: impl Into<WidgetId>, value: f32) -> Result<(), GuiError> {
+        self.panel_mut(panel)?.set_fader_value(fader, value)
+    }
+
+    pub fn set_fader_value(&mut self, fader: impl Into<WidgetId>, value: f32) -> Result<(), GuiError> {
+        self.default_panel_mut()?.set_fader_value(fader, value)
+    }
+
+    pub fn panel_set_button_label(&mut self, panel: &str, id: impl Into<WidgetId>, label: impl Into<Cow<'static, str>>) -> Result<(), GuiError> {
+        self.update_panel(panel, |panel| panel.set_button_label(id, label))
+    }
+
+    pub fn set_button_label(&mut self, id: impl Into<WidgetId>, label: impl Into<Cow<'static, str>>) -> Result<(), GuiError> {
+        let panel = self.default_panel()?.name.clone();
+        self.panel_set_button_label(&panel, id, label)
+    }
+
+    pub fn panel_set_textfield_label(&mut self, panel: &str, id: impl Into<WidgetId>, label: impl Into<Cow<'static, str>>) -> Result<(), GuiError> {
+        self.update_panel(panel, |panel| panel.set_textfield_label(id, label))
+    }
+
+    pub fn set_textfield_label(&mut self, id: impl Into<WidgetId>, label: impl Into<Cow<'static, str>>) -> Result<(), GuiError> {
+        let panel = self.default_panel()?.name.clone();
+        self.panel_set_textfield_label(&panel, id, label)
+    }
+
+    pub fn panel_set_dropdown_label(&mut self, panel: &str, id: impl Into<WidgetId>, label: impl Into<Cow<'static, str>>) -> Result<(), GuiError> {
+        self.update_panel(panel, |panel| panel.set_dropdown_label(id, label))
+    }
+
+    pub fn set_dropdown_label(&mut self, id: impl Into<WidgetId>, label: impl Into<Cow<'static, str>>) -> Result<(), GuiError> {
+        let panel = self.default_panel()?.name.clone();
+        self.panel_set_dropdown_label(&panel, id, label)
+    }
+
+    /// Replaces the options of a dropdown, e.g. with rows loaded at runtime
+    pub fn panel_set_dropdown_options<O>(&mut self, panel: &str, id: impl Into<WidgetId>, options: impl IntoIterator<Item = (O, T)>) -> Result<(), GuiError>
+    where
+        O: Into<Cow<'static, str>>,
+    {
+        self.update_panel(panel, |panel| panel.set_dropdown_options(id, options))
+    }
+
+    pub fn set_dropdown_options<O>(&mut self, id: impl Into<WidgetId>, options: impl IntoIterator<Item = (O, T)>) -> Result<(), GuiError>
+    where
+        O: Into<Cow<'static, str>>,
+    {
+        let panel = self.default_panel()?.name.clone();
+        self.panel_set_dropdown_options(&panel, id, options)
+    }
+
+    pub fn set_panel_title(&mut self, panel: &str, title: impl Into<Cow<'static, str>>) -> Result<(), GuiError> {
+        self.update_panel(panel, |panel| {
+            panel.set_title(title);
+            Ok(())
+        })
+    }
+
+    /// The state passed to callbacks. `None` while a callback runs, since the callback gets it as an argument.
+    pub fn state(&self) -> Option<&S> {
+        self.state.as_ref()
+    }
+
+    pub fn state_mut(&mut self) -> Option<&mut S> {
+        self.state.as_mut()
+    }
+
+    /// Calls `f` with the button's callback value from `poll` when the button is clicked,
+    /// before `GuiEvent::ButtonPress` is returned
+    pub fn on_click<F>(&mut self, panel: &str, id: impl Into<WidgetId>, f: F) -> Result<(), GuiError>
+    where
+        F: FnMut(&mut GUI<T, S>, &mut S, T) + 'static,
+    {
+        self.register(panel, WidgetType::Button, id, Callback::Click(Box::new(f)))
+    }
+
+    /// Calls `f` with the new value and the fader's callback value whenever the fader moves
+    pub fn on_change<F>(&mut self, panel: &str, id: impl Into<WidgetId>, f: F) -> Result<(), GuiError>
+    where
+        F: FnMut(&mut GUI<T, S>, &mut S, f32, T) + 'static,
+    {
+        self.register(panel, WidgetType::Fader, id, Callback::Change(Box::new(f)))
+    }
+
+    /// Calls `f` with the label and value of the chosen option whenever an option of the dropdown is selected
+    pub fn on_select<F>(&mut self, panel: &str, id: impl Into<WidgetId>, f: F) -> Result<(), GuiError>
+    where
+        F: FnMut(&mut GUI<T, S>, &mut S, &str, T) + 'static,
+    {
+        self.register(panel, WidgetType::DropdownButton, id, Callback::Select(Box::new(f)))
+    }
+
+    /// Calls `f` with the content and callback value of the textfield when Return is pressed in it
+    pub fn on_submit<F>(&mut self, panel: &str, id: impl Into<WidgetId>, f: F) -> Result<(), GuiError>
+    where
+        F: FnMut(&mut GUI<T, S>, &mut S, String, T) + 'static,
+    {
+        self.register(panel, WidgetType::TextField, id, Callback::Submit(Box::new(f)))
     }
 
+    // Callbacks are keyed by the ID the widget reports in events, so one registered by index
+    // still runs for a widget that has a user-assigned ID
+    fn register(&mut self, panel: &str, w_type: WidgetType, id: impl Into<WidgetId>, callback: Callback<T, S>) -> Result<(), GuiError> {
+        let panel = self.panel(panel)?;
+        let id = panel.widget_id(w_type, panel.index_of(w_type, id)?);
+        self.callbacks.insert((panel.name.to_string(), w_type, id), Rc::new(RefCell::new(callback)));
+        Ok(())
+    }
+
+    /// Removes the last character from the active textfields of the panels receiving input
     pub fn pop_active_textfield(&mut self) {
-        self.panels.iter_mut().for_each(|panel|
-            panel.1.textfields.iter_mut().for_each(|tb| {
-                if tb.is_active() {
-                    tb.pop_char();
-                }
-            })
-        );
+        self.dirty = true;
+        for panel in input_panels(&self.modal, &self.active_panels) {
+            if let Some(panel) = self.panels.get_mut(panel) {
+                panel.pop_from_active_textfields();
+            }
+        }
     }
 
-    pub fn panel_push_to_textfield(&mut self, panel: &'static str, idx: usize, c: char) {
-        self.panels
-            .get_mut(panel)
-            .expect(&format!("Panel '{}' doesn't exist", panel))
-            .push_to_textfield(idx, c);
+    pub fn panel_push_to_textfield(&mut self, panel: &str, id: impl Into<WidgetId>, c: char) -> Result<(), GuiError> {
+        self.panel_mut(panel)?.push_to_textfield(id, c)
     }
 
-    pub fn push_to_textfield(&mut self, idx: usize, c: char) {
-        self.panels
-            .values_mut()
-            .nth(0)
-            .unwrap()
-            .push_to_textfield(idx, c);
+    pub fn push_to_textfield(&mut self, id: impl Into<WidgetId>, c: char) -> Result<(), GuiError> {
+        self.default_panel_mut()?.push_to_textfield(id, c)
+    }
+
+    pub fn show_panel(&mut self, panel: &str) -> Result<(), GuiError> {
+        let name = self.root(panel)?.name.clone();
+        if !self.active_panels.contains(&name) {
+            self.active_panels.push(name);
+            self.sort_visible();
+            self.dirty = true;
+        }
+        Ok(())
+    }
+
+    /// Moves `panel` above all other panels
+    pub fn raise_panel(&mut self, panel: &str) -> Result<(), GuiError> {
+        let name = self.root(panel)?.name.clone();
+        self.panel_order.retain(|p| *p != name);
+        self.panel_order.push(name);
+        self.sort_visible();
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Moves `panel` below all other panels, making it the default panel
+    pub fn lower_panel(&mut self, panel: &str) -> Result<(), GuiError> {
+        let name = self.root(panel)?.name.clone();
+        self.panel_order.retain(|p| *p != name);
+        self.panel_order.insert(0, name);
+        self.sort_visible();
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// All panels from bottom to top. Panels are drawn in this order and hit-tested in reverse.
+    /// Methods without a panel argument, such as `set_fader_value`, use the bottom panel.
+    pub fn panel_order(&self) -> &[Cow<'static, str>] {
+        &self.panel_order
     }
 
-    pub fn show_panel(&mut self, panel: &'static str) {
-        if !self.active_panels.contains(&panel) {
-            self.active_panels.push(panel);
+    // Keeps the visible panels in z-order
+    fn sort_visible(&mut self) {
+        let order = &self.panel_order;
+        self.active_panels.sort_by_key(|name| order.iter().position(|p| p == name));
+    }
+
+    /// Shows `panel` above all others and dims them. Only the modal panel receives input until
+    /// it's closed with `close_modal` or hidden.
+    pub fn show_modal(&mut self, panel: &str) -> Result<(), GuiError> {
+        self.show_panel(panel)?;
+        self.deselect_all();
+        self.modal = Some(self.root(panel)?.name.clone());
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Hides the modal panel, if any, and returns input to the other panels
+    pub fn close_modal(&mut self) {
+        if let Some(modal) = self.modal.clone() {
+            let _ = self.hide_panel(&modal);
         }
     }
 
-    pub fn hide_panel(&mut self, panel: &'static str) {
+    pub fn modal(&self) -> Option<&str> {
+        self.modal.as_deref()
+    }
+
+    pub fn hide_panel(&mut self, panel: &str) -> Result<(), GuiError> {
+        self.root(panel)?;
+        // A hidden panel keeps no focus, so typing and Tab don't reach it
+        if self.active_widget.as_ref().is_some_and(|widget| widget.0.root() == panel) {
+            self.deselect_all();
+        }
+        if self.modal.as_deref() == Some(panel) {
+            self.modal = None;
+            self.dirty = true;
+        }
         if let Some(index) = self.active_panels.iter().position(|p| *p == panel) {
             self.active_panels.remove(index);
+            self.dirty = true;
         }
+        Ok(())
     }
 
-    pub fn panel_clear_textfield(&mut self, panel: &'static str, idx: usize) {
-        self.panels
-            .get_mut(panel)
-            .expect(&format!("Panel '{}' doesn't exist", panel))
-            .clear_textfield(idx);
+    /// Adds a top-level panel above all others and shows it
+    pub fn add_panel(&mut self, mut panel: Panel<T>) -> Result<(), GuiError> {
+        if let Some(existing) = panel.descendants().into_iter().find(|p| self.panel(&p.name).is_ok()) {
+            return Err(GuiError::DuplicatePanel(existing.name.to_string()));
+        }
+        panel.relayout(self.window_size);
+        let name = panel.name.clone();
+        self.panels.insert(name.clone(), panel);
+        self.panel_order.push(name.clone());
+        self.active_panels.push(name);
+        self.sort_visible();
+        self.dirty = true;
+        Ok(())
     }
 
-    pub fn clear_textfield(&mut self, idx: usize) {
-        self.panels
-            .values_mut()
-            .nth(0)
-            .unwrap()
-            .clear_textfield(idx);
+    /// Removes a top-level or nested panel along with its widgets and children
+    pub fn remove_panel(&mut self, panel: &str) -> Result<(), GuiError> {
+        let path = self.path_to(panel)?;
+        match path.panels().len() {
+            1 => {
+                self.panels.remove(panel);
+                self.panel_order.retain(|p| *p != panel);
+                self.active_panels.retain(|p| *p != panel);
+                if self.modal.as_deref() == Some(panel) {
+                    self.modal = None;
+                }
+            },
+            _ => {
+                if let Some(root) = self.panels.get_mut(path.root()) {
+                    root.remove_descendant(panel);
+                }
+                self.relayout_root(&path);
+            },
+        }
+        if self.active_widget.as_ref().is_some_and(|widget| widget.0.contains(panel)) {
+            self.active_widget = None;
+        }
+        self.handler.panel_removed(panel);
+        let removed: Vec<_> = self.callbacks
+            .keys()
+            .filter(|(name, ..)| self.path_to(name).is_err())
+            .cloned()
+            .collect();
+        for key in removed {
+            self.callbacks.remove(&key);
+        }
+        self.dirty = true;
+        Ok(())
     }
 
-    fn deselect_all(&mut self) {
-        if let Some(widget) = self.active_widget {
-            self.panels.get_mut(widget.0).unwrap().deselect(widget.1, widget.2);
-            self.active_widget = None
+    /// Adds a widget to a top-level or nested panel, positioned relative to it.
+    /// Returns the type and index the widget is addressed by.
+    pub fn add_widget(&mut self, panel: &str, widget: impl Into<AnyWidget<T>>) -> Result<(WidgetType, usize), GuiError> {
+        self.update_panel(panel, |panel| Ok(panel.add_widget(widget)))
+    }
+
+    /// Removes a widget from a top-level or nested panel and returns it.
+    /// Later widgets of the same type in that panel move down one index.
+    pub fn remove_widget(&mut self, panel: &str, w_type: WidgetType, id: impl Into<WidgetId>) -> Result<AnyWidget<T>, GuiError> {
+        let path = self.path_to(panel)?;
+        let idx = self.panel(panel)?.index_of(w_type, id)?;
+        let id = self.panel(panel)?.widget_id(w_type, idx);
+        let removed = self.panel_mut(panel)?.remove_widget(w_type, idx)?;
+        callback::widget_removed(&mut self.callbacks, panel, w_type, id, idx);
+        self.active_widget = self.active_widget
+            .take()
+            .and_then(|widget| after_removal(widget, &path, (w_type, idx)));
+        self.handler.widget_removed(&path, (w_type, idx));
+        self.relayout_root(&path);
+        Ok(removed)
+    }
+
+    pub fn panel_clear_textfield(&mut self, panel: &str, id: impl Into<WidgetId>) -> Result<(), GuiError> {
+        self.panel_mut(panel)?.clear_textfield(id)
+    }
+
+    pub fn clear_textfield(&mut self, id: impl Into<WidgetId>) -> Result<(), GuiError> {
+        self.default_panel_mut()?.clear_textfield(id)
+    }
+
+    /// Size of the window in unscaled pixels, the coordinate space widgets are placed in
+    pub const fn window_size(&self) -> (u32, u32) {
+        self.window_size
+    }
+
+    pub const fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Scales geometry, text and hit-testing by `scale`, resizing the window to match
+    pub fn set_scale(&mut self, scale: f32) -> Result<(), GuiError> {
+        if !(MIN_SCALE..=MAX_SCALE).contains(&scale) {
+            return Err(GuiError::ValueOutOfRange { value: scale, min: MIN_SCALE, max: MAX_SCALE });
         }
-    } 
+        let (w, h) = self.window_size;
+        self.canvas
+            .window_mut()
+            .set_size((w as f32 * scale) as u32, (h as f32 * scale) as u32)?;
+        self.canvas.set_scale(scale, scale)?;
+        self.fonts.set_scale(scale);
+        self.handler.set_scale(scale);
+        self.scale = scale;
+        self.dirty = true;
+        Ok(())
+    }
 
-    fn unhover_widget(&mut self, widget: WidgetData) {
+    // Recomputes anchored panels and relatively sized widgets from the window's size in pixels
+    fn resize(&mut self, w: u32, h: u32) {
+        let size = ((w as f32 / self.scale) as u32, (h as f32 / self.scale) as u32);
+        self.window_size = size;
         self.panels
-            .get_mut(widget.0)
-            .unwrap()
-            .unhover(widget.1, widget.2)
+            .values_mut()
+            .for_each(|panel| panel.relayout(size));
+    }
 
+    // Only top-level panels can be shown, hidden and reordered
+    fn root(&self, panel: &str) -> Result<&Panel<T>, GuiError> {
+        self.panels
+            .get(panel)
+            .ok_or_else(|| GuiError::UnknownPanel(panel.to_string()))
     }
 
-    fn hover_widget(&mut self, widget: WidgetData) {
+    fn path_to(&self, panel: &str) -> Result<PanelPath, GuiError> {
         self.panels
-            .get_mut(widget.0)
-            .unwrap()
-            .hover(widget.1, widget.2);
+            .values()
+            .find_map(|root| root.path_to(panel))
+            .ok_or_else(|| GuiError::UnknownPanel(panel.to_string()))
     }
 
-    fn drag(&mut self, widget: WidgetData, x: i32, y: i32) -> Option<f32> {
+    // Changes a top-level or nested panel, then fits the panels around it to its new size
+    fn update_panel<R>(&mut self, panel: &str, f: impl FnOnce(&mut Panel<T>) -> Result<R, GuiError>) -> Result<R, GuiError> {
+        let path = self.path_to(panel)?;
+        let result = f(self.panel_mut(panel)?)?;
+        self.relayout_root(&path);
+        Ok(result)
+    }
+
+    // Parents fit around their children, and anchored panels are placed by their size
+    fn relayout_root(&mut self, path: &PanelPath) {
+        let window = self.window_size;
+        if let Some(root) = self.panels.get_mut(path.root()) {
+            root.relayout(window);
+        }
+    }
+
+    fn panel_at_path_mut(&mut self, path: &PanelPath) -> Option<&mut Panel<T>> {
         self.panels
-            .get_mut(widget.0)
-            .unwrap()
-            .drag(widget.1, widget.2, x, y)
+            .get_mut(path.root())
+            .and_then(|root| root.descendant_mut(path))
+    }
+
+    // The panel holding `widget`
+    fn owner_mut(&mut self, widget: &WidgetData) -> Option<&mut Panel<T>> {
+        self.panel_at_path_mut(&widget.0)
+    }
+
+    fn default_panel(&self) -> Result<&Panel<T>, GuiError> {
+        self.panel_order
+            .first()
+            .and_then(|name| self.panels.get(name))
+            .ok_or(GuiError::NoPanels)
+    }
+
+    fn default_panel_mut(&mut self) -> Result<&mut Panel<T>, GuiError> {
+        self.dirty = true;
+        self.panel_order
+            .first()
+            .and_then(|name| self.panels.get_mut(name))
+            .ok_or(GuiError::NoPanels)
+    }
+
+    fn deselect_all(&mut self) {
+        if let Some(widget) = self.active_widget.take() {
+            if let Some(panel) = self.owner_mut(&widget) {
+                panel.deselect(widget.1, widget.2);
+            }
+        }
+    }
+}
+
+// While a modal panel is open, it's the only one receiving input
+fn input_panels<'a>(modal: &'a Option<Cow<'static, str>>, active_panels: &'a [Cow<'static, str>]) -> &'a [Cow<'static, str>] {
+    match modal {
+        Some(modal) => std::slice::from_ref(modal),
+        None => active_panels,
     }
 }
 
 // GuiBuilder
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct GuiBuilder<T>
 where
     T: Copy,
@@ -278,12 +798,21 @@ where
     window_size: (u32, u32),
     backround_color: Color,
     window_title: &'static str,
-    panels: HashMap<&'static str, Panel<T>>,
-    active_panels: Vec<&'static str>,
+    font: FontSource,
+    panels: HashMap<Cow<'static, str>, Panel<T>>,
+    panel_order: Vec<Cow<'static, str>>,
+    active_panels: Vec<Cow<'static, str>>,
+    // A panel passed to `panels` whose name was already taken, reported by `build`
+    duplicate_panel: Option<Cow<'static, str>>,
     buttons: Vec<Button<T>>,
-    textfields: Vec<TextField>,
-    faders: Vec<Fader>,
+    textfields: Vec<TextField<T>>,
+    faders: Vec<Fader<T>>,
     quit_on_escape: bool,
+    headless: bool,
+    debug: bool,
+    resizable: bool,
+    scale: f32,
+    zoom_shortcuts: bool,
 }
 impl<T> GuiBuilder<T>
 where
@@ -294,12 +823,20 @@ where
             window_size: (800, 600),
             backround_color: BACKROUNDCOLOR,
             window_title: "",
+            font: FontSource::Embedded,
             panels: HashMap::new(),
+            panel_order: vec![],
             active_panels: vec![],
+            duplicate_panel: None,
             buttons: vec![],
             textfields: vec![],
             faders: vec![],
-            quit_on_escape: false
+            quit_on_escape: false,
+            headless: false,
+            debug: false,
+            resizable: false,
+            scale: 1.0,
+            zoom_shortcuts: false,
         }
     }
     pub const fn color(mut self, rgb: (u8, u8, u8)) -> GuiBuilder<T> {
@@ -310,15 +847,26 @@ where
         self.window_title = s;
         self
     }
+    /// Loads the default font of the GUI from a file instead of using the embedded one.
+    /// `build()` fails with `GuiError::FontLoad` if the file can't be loaded.
+    pub fn font<P: Into<PathBuf>>(mut self, path: P) -> GuiBuilder<T> {
+        self.font = FontSource::File(path.into());
+        self
+    }
+    /// Loads the default font of the GUI from memory, e.g. `include_bytes!("font.ttf")`
+    pub fn font_bytes(mut self, bytes: &'static [u8]) -> GuiBuilder<T> {
+        self.font = FontSource::Bytes(bytes);
+        self
+    }
     pub fn buttons(mut self, buttons: Vec<Button<T>>) -> GuiBuilder<T> {
         self.buttons = buttons;
         self
     }
-    pub fn textfields(mut self, tb: Vec<TextField>) -> GuiBuilder<T> {
+    pub fn textfields(mut self, tb: Vec<TextField<T>>) -> GuiBuilder<T> {
         self.textfields = tb;
         self
     }
-    pub fn faders(mut self, faders: Vec<Fader>) -> GuiBuilder<T> {
+    pub fn faders(mut self, faders: Vec<Fader<T>>) -> GuiBuilder<T> {
         self.faders = faders;
         self
     }
@@ -331,52 +879,132 @@ where
         self.quit_on_escape = true;
         self
     }
+    /// Renders into an offscreen software framebuffer instead of a visible window.
+    /// Uses the SDL dummy video driver unless `SDL_VIDEODRIVER` is set.
+    pub const fn headless(mut self) -> GuiBuilder<T> {
+        self.headless = true;
+        self
+    }
+    /// Lets the user resize the window. See `Panel::anchor` and `Panel::relative_size`.
+    pub const fn resizable(mut self) -> GuiBuilder<T> {
+        self.resizable = true;
+        self
+    }
+    /// Scales the whole GUI, e.g. by 2.0 on high-density displays. Sizes given to widgets,
+    /// panels and `size` stay in unscaled pixels.
+    pub const fn scale(mut self, scale: f32) -> GuiBuilder<T> {
+        self.scale = scale;
+        self
+    }
+    /// Enables Ctrl+Plus, Ctrl+Minus and Ctrl+0 to zoom in, zoom out and reset the scale
+    pub const fn zoom_shortcuts(mut self) -> GuiBuilder<T> {
+        self.zoom_shortcuts = true;
+        self
+    }
+    /// Starts the GUI with the debug overlay enabled
+    pub const fn debug(mut self) -> GuiBuilder<T> {
+        self.debug = true;
+        self
+    }
+    /// Adds panels from bottom to top
     pub fn panels(mut self, panels: &[Panel<T>]) -> GuiBuilder<T> {
         for panel in panels {
-            self.panels.insert(panel.name, panel.clone());
+            match self.panels.insert(panel.name.clone(), panel.clone()) {
+                None => self.panel_order.push(panel.name.clone()),
+                Some(_) => self.duplicate_panel = Some(panel.name.clone()),
+            }
         }
         self
     }
-    pub fn initial_panels(mut self, active: &[&'static str]) -> GuiBuilder<T> {
-        self.active_panels.extend_from_slice(active);
+    pub fn initial_panels(mut self, active: &[&str]) -> GuiBuilder<T> {
+        self.active_panels.extend(active.iter().map(|name| Cow::Owned(name.to_string())));
         self
     }
 
-    pub fn build(mut self) -> Result<GUI<T>, String> {
+    pub fn build(self) -> Result<GUI<T>, GuiError> {
+        self.build_with_state(())
+    }
+
+    /// Panel names must be unique across all panels and their children
+    fn check_unique_names(&self) -> Result<(), GuiError> {
+        if let Some(panel) = &self.duplicate_panel {
+            return Err(GuiError::DuplicatePanel(panel.to_string()));
+        }
+        let mut names = HashSet::new();
+        match self.panels.values().flat_map(|panel| panel.descendants()).find(|panel| !names.insert(&panel.name)) {
+            Some(duplicate) => Err(GuiError::DuplicatePanel(duplicate.name.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Builds the GUI with a state object that is passed to closure callbacks, such as those
+    /// registered with `GUI::on_click`
+    pub fn build_with_state<S>(mut self, state: S) -> Result<GUI<T, S>, GuiError> {
+        self.check_unique_names()?;
+        if self.headless {
+            // SDL before 2.0.22 ignores the hint and only reads the environment variable
+            if std::env::var_os("SDL_VIDEODRIVER").is_none() {
+                std::env::set_var("SDL_VIDEODRIVER", "dummy");
+            }
+            sdl2::hint::set("SDL_VIDEODRIVER", "dummy");
+        }
         let sdl_context = sdl2::init()?;
-        let ttf_context = sdl2::ttf::init().map_err(|e| e.to_string())?;
-        let canvas = sdl_context
+        let ttf_context = sdl2::ttf::init().map_err(|e| GuiError::FontLoad(e.to_string()))?;
+        let mut window = sdl_context
             .video()?
-            .window(&self.window_title, self.window_size.0, self.window_size.1)
-            .position_centered()
-            .build()
-            .map_err(|e| e.to_string())?
-            .into_canvas()
-            .build()
-            .map_err(|e| e.to_string())?;
+            .window(&self.window_title, self.window_size.0, self.window_size.1);
+        if self.resizable {
+            window.resizable();
+        }
+        let canvas_builder = match self.headless {
+            true => window.hidden().build()?.into_canvas().software(),
+            false => window.position_centered().build()?.into_canvas(),
+        };
+        let canvas = canvas_builder.build()?;
 
 
         if self.panels.is_empty() {
             self.panels.insert(
-                "default", 
+                Cow::Borrowed("default"), 
                 Panel::new("default", (0, 0), self.buttons, self.textfields, self.faders, vec![])
             );
+            self.panel_order.push(Cow::Borrowed("default"));
         }
 
         if self.active_panels.is_empty() {
-            for i in self.panels.iter() {
-                self.active_panels.push(i.0);
-            }
+            self.active_panels = self.panel_order.clone();
+        }
+
+        let window_size = canvas.window().size();
+        for panel in self.panels.values_mut() {
+            panel.relayout(window_size);
         }
 
-        return Ok(GUI {
-            ttf_context,
+        let fonts = FontManager::new(ttf_context, canvas.texture_creator());
+        fonts.check(&self.font)?;
+
+        let mut gui = GUI {
+            fonts,
+            font: self.font,
             canvas,
             backround_color: self.backround_color,
-            handler: EventHandler::new(&sdl_context, self.quit_on_escape)?,
+            handler: EventHandler::new(&sdl_context, self.quit_on_escape, self.zoom_shortcuts)?,
             panels: self.panels,
+            panel_order: self.panel_order,
             active_panels: self.active_panels,
+            modal: None,
             active_widget: None,
-        });
+            headless: self.headless,
+            debug: DebugOverlay::new(self.debug),
+            window_size,
+            scale: 1.0,
+            base_scale: self.scale,
+            dirty: true,
+            state: Some(state),
+            callbacks: HashMap::new(),
+        };
+        gui.sort_visible();
+        gui.set_scale(self.scale)?;
+        Ok(gui)
     }
 }
\ No newline at end of file
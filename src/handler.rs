@@ -1,116 +1,241 @@
-use crate::widgets::{WidgetData, WidgetType};
-use crate::{Panel, in_bounds, Direction};
+use crate::widgets::{Orientation, WidgetData, WidgetType};
+use crate::panel::reindex;
+use crate::{Panel, PanelPath, in_bounds, Direction, GuiError};
 
-use sdl2::event::Event;
+use sdl2::event::{Event, WindowEvent};
 use sdl2::keyboard::{Keycode, Mod};
-use sdl2::mouse::MouseButton;
+use sdl2::mouse::{MouseButton, MouseWheelDirection};
 use sdl2::{EventPump, Sdl};
 
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::time::Duration;
+
+// Pixels scrolled per notch of the mouse wheel
+const SCROLL_STEP: i32 = 20;
 
 pub struct EventHandler {
     pump: EventPump,
-    active_panel: Option<&'static str>,
+    active_panel: Option<Cow<'static, str>>,
     lmb_pressed_on: Option<WidgetData>,
     hovered: Option<WidgetData>,
-    quit_on_escape: bool
+    // Panel being dragged by its title bar and the last mouse position
+    dragged_panel: Option<(Cow<'static, str>, i32, i32)>,
+    close_pressed_on: Option<Cow<'static, str>>,
+    // Panel whose scrollbar is being dragged and where the thumb was grabbed
+    dragged_scrollbar: Option<(PanelPath, Orientation, i32)>,
+    quit_on_escape: bool,
+    zoom_shortcuts: bool,
+    scale: f32,
 }
 
 impl EventHandler {
-    pub fn new(context: &Sdl, quit_on_escape: bool) -> Result<EventHandler, String> {
+    pub fn new(context: &Sdl, quit_on_escape: bool, zoom_shortcuts: bool) -> Result<EventHandler, GuiError> {
         Ok(EventHandler {
             pump: context.event_pump()?,
             active_panel: None,
             hovered: None,
             lmb_pressed_on: None,
-            quit_on_escape
+            dragged_panel: None,
+            close_pressed_on: None,
+            dragged_scrollbar: None,
+            quit_on_escape,
+            zoom_shortcuts,
+            scale: 1.0,
         })
     }
 
-    pub fn poll_blocking<T: Copy + Default>(&mut self, panels: &mut HashMap<&'static str, Panel<T>>, visible_panels: &Vec<&'static str>) -> HandlerEvent {
-        match self.pump.wait_event() {
+    /// Mouse coordinates are divided by `scale` before hit-testing
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+    }
+
+    fn unscale(&self, x: i32, y: i32) -> (i32, i32) {
+        ((x as f32 / self.scale) as i32, (y as f32 / self.scale) as i32)
+    }
+
+    pub fn poll_blocking<T: Copy + Default>(&mut self, panels: &mut HashMap<Cow<'static, str>, Panel<T>>, visible_panels: &[Cow<'static, str>]) -> HandlerEvent {
+        let event = self.pump.wait_event();
+        self.handle(event, panels, visible_panels)
+    }
+
+    /// Returns `None` if no events are pending.
+    pub fn poll_nonblocking<T: Copy + Default>(&mut self, panels: &mut HashMap<Cow<'static, str>, Panel<T>>, visible_panels: &[Cow<'static, str>]) -> Option<HandlerEvent> {
+        let event = self.pump.poll_event()?;
+        Some(self.handle(event, panels, visible_panels))
+    }
+
+    /// Returns `None` if no event arrived before the timeout.
+    pub fn poll_timeout<T: Copy + Default>(&mut self, panels: &mut HashMap<Cow<'static, str>, Panel<T>>, visible_panels: &[Cow<'static, str>], timeout: Duration) -> Option<HandlerEvent> {
+        let timeout = timeout.as_millis().min(u32::MAX as u128) as u32;
+        let event = self.pump.wait_event_timeout(timeout)?;
+        Some(self.handle(event, panels, visible_panels))
+    }
+
+    pub fn handle<T: Copy + Default>(&mut self, event: Event, panels: &mut HashMap<Cow<'static, str>, Panel<T>>, visible_panels: &[Cow<'static, str>]) -> HandlerEvent {
+        match event {
             Event::Quit { .. } => HandlerEvent::Quit,
+            Event::Window { win_event: WindowEvent::Resized(w, h), .. } => {
+                HandlerEvent::Resize(w.max(0) as u32, h.max(0) as u32)
+            },
+            Event::Window { win_event: WindowEvent::Exposed, .. } => HandlerEvent::Redraw,
             Event::TextInput { text, .. } => HandlerEvent::TextInput(text),
             Event::KeyDown { keycode, keymod, .. } => {
                 self.parse_keycode(keycode, keymod)
             },
+            Event::MouseWheel { x, y, direction, mouse_x, mouse_y, .. } => {
+                let (mouse_x, mouse_y) = self.unscale(mouse_x, mouse_y);
+                let flip = match direction {
+                    MouseWheelDirection::Flipped => -1,
+                    _ => 1,
+                };
+                // Wheel up scrolls towards the top
+                visible_panels
+                    .iter()
+                    .rev()
+                    .filter_map(|name| panels.get(name))
+                    .find(|panel| in_bounds(&panel.bounds, mouse_x, mouse_y))
+                    .and_then(|panel| panel.scrollable_at(mouse_x, mouse_y))
+                    .map(|path| HandlerEvent::Scroll(path, flip * x * SCROLL_STEP, -flip * y * SCROLL_STEP))
+                    .unwrap_or(HandlerEvent::None)
+            },
             Event::MouseMotion { x, y, .. } => {
+                let (x, y) = self.unscale(x, y);
+                if let Some((panel, orientation, grab)) = &self.dragged_scrollbar {
+                    return HandlerEvent::DragScrollbar(panel.clone(), *orientation, scrollbar_position(*orientation, x, y) - grab)
+                }
+                if let Some((panel, last_x, last_y)) = self.dragged_panel.take() {
+                    self.dragged_panel = Some((panel.clone(), x, y));
+                    return HandlerEvent::MovePanel(panel, x - last_x, y - last_y)
+                }
                 // If something is pressed, we are dragging it
-                if let Some(widget_data) = self.lmb_pressed_on {
-                    return HandlerEvent::Drag(widget_data, x, y)
+                if let Some(widget_data) = &self.lmb_pressed_on {
+                    return HandlerEvent::Drag(widget_data.clone(), x, y)
                 }
 
+                // Popups take priority, then the topmost panel under the cursor.
+                // Visible panels are ordered from bottom to top.
                 // If we are not on any panel, we aren't hovering anything
-                self.active_panel = panels
+                let topmost_first = || visible_panels
                     .iter()
-                    .find(|panel| in_bounds(&panel.1.bounds, x, y) && visible_panels.contains(panel.0))
-                    .map(|panel| *panel.0);
-                if self.active_panel.is_none() {
-                    if self.hovered.is_some() {
-                        let hovered = self.hovered.unwrap();
-                        self.hovered = None;
-                        return HandlerEvent::UnHover(hovered);
+                    .rev()
+                    .filter_map(|name| panels.get_key_value(name));
+                self.active_panel = topmost_first()
+                    .find(|panel| panel.1.overlay_at(x, y).is_some())
+                    .or_else(|| topmost_first().find(|panel| in_bounds(&panel.1.bounds, x, y)))
+                    .map(|panel| panel.0.clone());
+                let Some(active_panel) = &self.active_panel else {
+                    return match self.hovered.take() {
+                        Some(hovered) => HandlerEvent::UnHover(hovered),
+                        None => HandlerEvent::None,
                     }
-                    return HandlerEvent::None
-                }
+                };
 
                 // We are hovering a panel
-                let active_panel = self.active_panel.unwrap();
-                let hovered = panels[active_panel].get_widget_data(x, y);
-                if hovered.is_some() 
-                && hovered.unwrap().1 == WidgetType::DropdownButton {
-                    self.hovered = hovered;
-                    return HandlerEvent::HoverDropdown(hovered.unwrap(), x, y);
-                }
-                if hovered.is_some() && self.hovered != hovered {
-                    self.hovered = hovered;
-                    return HandlerEvent::Hover(hovered.unwrap());
-                } else if hovered.is_none() && self.hovered.is_some() {
-                    let unhovered = self.hovered.unwrap();
-                    self.hovered = None;
-                    return HandlerEvent::UnHover(unhovered);
+                match panels[active_panel].get_widget_data(x, y) {
+                    Some(hovered) if hovered.1 == WidgetType::DropdownButton => {
+                        self.hovered = Some(hovered.clone());
+                        HandlerEvent::HoverDropdown(hovered, x, y)
+                    },
+                    Some(hovered) if self.hovered.as_ref() != Some(&hovered) => {
+                        self.hovered = Some(hovered.clone());
+                        HandlerEvent::Hover(hovered)
+                    },
+                    Some(_) => HandlerEvent::None,
+                    None => match self.hovered.take() {
+                        Some(unhovered) => HandlerEvent::UnHover(unhovered),
+                        None => HandlerEvent::None,
+                    },
                 }
-                
-                HandlerEvent::None
             },
             Event::MouseButtonDown { mouse_btn, x, y, .. } => {
                 if mouse_btn != MouseButton::Left {
                     return HandlerEvent::None
                 }
-                if self.active_panel.is_none() {
+                let (x, y) = self.unscale(x, y);
+                // The panel may have been hidden or lost input to a modal one since the mouse last moved
+                let Some(active_panel) = self.active_panel.clone().filter(|panel| visible_panels.contains(panel)) else {
                     return HandlerEvent::None
+                };
+                let panel = &panels[&active_panel];
+                if let Some((path, orientation)) = panel.scrollbar_at(x, y) {
+                    let grab = panel
+                        .descendant(&path)
+                        .map_or(0, |scrolled| scrolled.scrollbar_grab(orientation, x, y));
+                    self.dragged_scrollbar = Some((path.clone(), orientation, grab));
+                    return HandlerEvent::DragScrollbar(path, orientation, scrollbar_position(orientation, x, y) - grab)
+                }
+                if panel.close_button_at(x, y) {
+                    self.close_pressed_on = Some(active_panel.clone());
+                } else if panel.drag_handle_at(x, y) {
+                    self.dragged_panel = Some((active_panel.clone(), x, y));
+                } else {
+                    self.lmb_pressed_on = panel.get_widget_data(x, y);
                 }
-                let active_panel = self.active_panel.unwrap();
-                let widget_data = panels[active_panel].get_widget_data(x, y);
-
-                self.lmb_pressed_on = widget_data;
 
-                HandlerEvent::None
+                HandlerEvent::PanelPressed(active_panel)
             },
 
             Event::MouseButtonUp { mouse_btn, x, y, .. } => {
                 if mouse_btn != MouseButton::Left {
                     return HandlerEvent::None
                 }
-                if self.active_panel.is_some() && self.lmb_pressed_on.is_some() {
-                    let widget = panels[self.active_panel.unwrap()].get_widget_data(x, y);
-                    let pressed_on = self.lmb_pressed_on.unwrap();
-                    self.lmb_pressed_on = None;
-                    if widget == Some(pressed_on) {
-                        return HandlerEvent::Click(widget.unwrap());
+                let (x, y) = self.unscale(x, y);
+                if self.dragged_panel.take().is_some() || self.dragged_scrollbar.take().is_some() {
+                    return HandlerEvent::None
+                }
+                if let Some(panel) = self.close_pressed_on.take() {
+                    if panels.get(&panel).is_some_and(|panel| panel.close_button_at(x, y)) {
+                        return HandlerEvent::ClosePanel(panel)
                     }
-                    return HandlerEvent::UnHover(pressed_on);
-                } else {
-                    return HandlerEvent::ClickBackround
+                    return HandlerEvent::None
+                }
+                match (&self.active_panel, self.lmb_pressed_on.take()) {
+                    (Some(active_panel), Some(pressed_on)) => {
+                        if panels[active_panel].get_widget_data(x, y).as_ref() == Some(&pressed_on) {
+                            return HandlerEvent::Click(pressed_on);
+                        }
+                        HandlerEvent::UnHover(pressed_on)
+                    },
+                    _ => HandlerEvent::ClickBackround,
                 }
             }
             _ => HandlerEvent::None,
         }
     }
 
+    pub const fn hovered(&self) -> Option<&WidgetData> {
+        self.hovered.as_ref()
+    }
+
+    pub const fn pressed(&self) -> Option<&WidgetData> {
+        self.lmb_pressed_on.as_ref()
+    }
+
+    /// Keeps the hovered and pressed widgets pointing at the same widgets after one is removed
+    pub fn widget_removed(&mut self, path: &PanelPath, removed: (WidgetType, usize)) {
+        self.hovered = self.hovered.take().and_then(|widget| after_removal(widget, path, removed));
+        self.lmb_pressed_on = self.lmb_pressed_on.take().and_then(|widget| after_removal(widget, path, removed));
+    }
+
+    /// Forgets everything the mouse is doing with a removed panel or its widgets
+    pub fn panel_removed(&mut self, panel: &str) {
+        let contains = |path: &PanelPath| path.contains(panel);
+        self.active_panel = self.active_panel.take().filter(|active| active != panel);
+        self.close_pressed_on = self.close_pressed_on.take().filter(|pressed| pressed != panel);
+        self.hovered = self.hovered.take().filter(|widget| !contains(&widget.0));
+        self.lmb_pressed_on = self.lmb_pressed_on.take().filter(|widget| !contains(&widget.0));
+        self.dragged_panel = self.dragged_panel.take().filter(|dragged| dragged.0 != panel);
+        self.dragged_scrollbar = self.dragged_scrollbar.take().filter(|scrollbar| !contains(&scrollbar.0));
+    }
+
     const fn parse_keycode(&self, kc: Option<Keycode>, km: Mod) -> HandlerEvent {
+        let ctrl = km.contains(Mod::LCTRLMOD) || km.contains(Mod::RCTRLMOD);
         if let Some(keycode) = kc {
             return match keycode {
+                Keycode::Plus | Keycode::Equals | Keycode::KpPlus if self.zoom_shortcuts && ctrl => HandlerEvent::ZoomIn,
+                Keycode::Minus | Keycode::KpMinus if self.zoom_shortcuts && ctrl => HandlerEvent::ZoomOut,
+                Keycode::Num0 | Keycode::Kp0 if self.zoom_shortcuts && ctrl => HandlerEvent::ZoomReset,
                 Keycode::Backspace => HandlerEvent::PopChar,
                 Keycode::Return    => HandlerEvent::Return,
                 Keycode::F12       => HandlerEvent::ToggleDebug,
@@ -134,6 +259,22 @@ impl EventHandler {
 
 }
 
+fn scrollbar_position(orientation: Orientation, x: i32, y: i32) -> i32 {
+    match orientation {
+        Orientation::Vertical => y,
+        Orientation::Horizontal => x,
+    }
+}
+
+/// `widget` after `removed` was taken out of the panel at `path`
+pub(crate) fn after_removal(widget: WidgetData, path: &PanelPath, removed: (WidgetType, usize)) -> Option<WidgetData> {
+    if widget.0 != *path {
+        return Some(widget);
+    }
+    let (w_type, idx) = reindex((widget.1, widget.2), removed)?;
+    Some((widget.0, w_type, idx))
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum HandlerEvent {
     Quit,
@@ -151,7 +292,15 @@ pub enum HandlerEvent {
     ClickBackround,
     Tab,
     ShitTab,
+    Resize(u32, u32),
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    Redraw,
+    PanelPressed(Cow<'static, str>),
+    MovePanel(Cow<'static, str>, i32, i32),
+    ClosePanel(Cow<'static, str>),
+    Scroll(PanelPath, i32, i32),
+    DragScrollbar(PanelPath, Orientation, i32),
     None
 }
-
-
@@ -0,0 +1,239 @@
+use crate::rect;
+use crate::widgets::Widget;
+
+use sdl2::rect::Rect;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+}
+
+impl Align {
+    // Offset of an item of size `item` inside a cell of size `cell`
+    const fn offset(&self, cell: u32, item: u32) -> i32 {
+        let free = cell.saturating_sub(item) as i32;
+        match self {
+            Align::Start => 0,
+            Align::Center => free / 2,
+            Align::End => free,
+        }
+    }
+
+    // Offsets from the end edge point inwards
+    const fn direction(&self) -> i32 {
+        match self {
+            Align::End => -1,
+            _ => 1,
+        }
+    }
+}
+
+/// Edge or corner of the window that a panel is positioned relative to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Top left corner of an item of `size` placed inside `area`, `offset` pixels away from the anchor
+    pub(crate) const fn place(&self, area: (u32, u32), size: (u32, u32), offset: (i32, i32)) -> (i32, i32) {
+        let (horizontal, vertical) = match self {
+            Anchor::TopLeft => (Align::Start, Align::Start),
+            Anchor::Top => (Align::Center, Align::Start),
+            Anchor::TopRight => (Align::End, Align::Start),
+            Anchor::Left => (Align::Start, Align::Center),
+            Anchor::Center => (Align::Center, Align::Center),
+            Anchor::Right => (Align::End, Align::Center),
+            Anchor::BottomLeft => (Align::Start, Align::End),
+            Anchor::Bottom => (Align::Center, Align::End),
+            Anchor::BottomRight => (Align::End, Align::End),
+        };
+        (
+            horizontal.offset(area.0, size.0) + horizontal.direction() * offset.0,
+            vertical.offset(area.1, size.1) + vertical.direction() * offset.1,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Row,
+    Column,
+    Grid(usize),
+}
+
+/// Positions widgets in a row, column or grid instead of at absolute coordinates.
+/// Widgets are measured by their visual bounds and moved with `Widget::shift`,
+/// so a layout can be applied before the widgets are given to a panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    kind: Kind,
+    origin: (i32, i32),
+    spacing: (i32, i32),
+    padding: i32,
+    align: (Align, Align),
+}
+
+impl Layout {
+    const fn new(kind: Kind) -> Layout {
+        Layout {
+            kind,
+            origin: (0, 0),
+            spacing: (0, 0),
+            padding: 0,
+            align: (Align::Start, Align::Start),
+        }
+    }
+    /// Lays widgets out horizontally
+    pub const fn row() -> Layout {
+        Layout::new(Kind::Row)
+    }
+    /// Lays widgets out vertically
+    pub const fn column() -> Layout {
+        Layout::new(Kind::Column)
+    }
+    /// Lays widgets out left to right, wrapping after `columns` widgets.
+    /// Each column is as wide as its widest widget and each row as tall as its tallest.
+    pub const fn grid(columns: usize) -> Layout {
+        Layout::new(Kind::Grid(columns))
+    }
+    /// Top left corner of the layout
+    pub const fn at(mut self, x: i32, y: i32) -> Layout {
+        self.origin = (x, y);
+        self
+    }
+    /// Gap between neighbouring widgets on both axes
+    pub const fn spacing(mut self, spacing: i32) -> Layout {
+        self.spacing = (spacing, spacing);
+        self
+    }
+    pub const fn spacing_xy(mut self, x: i32, y: i32) -> Layout {
+        self.spacing = (x, y);
+        self
+    }
+    /// Gap between the origin and the first row and column
+    pub const fn padding(mut self, padding: i32) -> Layout {
+        self.padding = padding;
+        self
+    }
+    /// Alignment of widgets that are smaller than their cell
+    pub const fn align(mut self, horizontal: Align, vertical: Align) -> Layout {
+        self.align = (horizontal, vertical);
+        self
+    }
+
+    /// Computes the rect of each item from its (width, height)
+    pub fn rects(&self, sizes: &[(u32, u32)]) -> Vec<Rect> {
+        let columns = match self.kind {
+            Kind::Row => sizes.len(),
+            Kind::Column => 1,
+            Kind::Grid(columns) => columns,
+        }
+        .max(1);
+        let rows = sizes.len().div_ceil(columns);
+
+        let mut widths = vec![0; columns];
+        let mut heights = vec![0; rows];
+        for (idx, (w, h)) in sizes.iter().enumerate() {
+            widths[idx % columns] = widths[idx % columns].max(*w);
+            heights[idx / columns] = heights[idx / columns].max(*h);
+        }
+
+        let xs = cell_starts(self.origin.0 + self.padding, &widths, self.spacing.0);
+        let ys = cell_starts(self.origin.1 + self.padding, &heights, self.spacing.1);
+
+        sizes
+            .iter()
+            .enumerate()
+            .map(|(idx, (w, h))| {
+                let (column, row) = (idx % columns, idx / columns);
+                rect!(
+                    xs[column] + self.align.0.offset(widths[column], *w),
+                    ys[row] + self.align.1.offset(heights[row], *h),
+                    *w,
+                    *h
+                )
+            })
+            .collect()
+    }
+
+    pub fn arrange<W: Widget>(&self, widgets: &mut [W]) {
+        let sizes: Vec<(u32, u32)> = widgets.iter().map(|widget| size(widget)).collect();
+        for (widget, rect) in widgets.iter_mut().zip(self.rects(&sizes)) {
+            move_to(widget, rect);
+        }
+    }
+
+    /// Arranges widgets of different types, e.g. a textfield above a button
+    pub fn arrange_dyn(&self, widgets: &mut [&mut dyn Widget]) {
+        let sizes: Vec<(u32, u32)> = widgets.iter().map(|widget| size(&**widget)).collect();
+        for (widget, rect) in widgets.iter_mut().zip(self.rects(&sizes)) {
+            move_to(&mut **widget, rect);
+        }
+    }
+}
+
+fn cell_starts(start: i32, lengths: &[u32], spacing: i32) -> Vec<i32> {
+    lengths
+        .iter()
+        .scan(start, |next, length| {
+            let current = *next;
+            *next += *length as i32 + spacing;
+            Some(current)
+        })
+        .collect()
+}
+
+fn size<W: Widget + ?Sized>(widget: &W) -> (u32, u32) {
+    let bounds = widget.visual_bounds();
+    (bounds.width(), bounds.height())
+}
+
+fn move_to<W: Widget + ?Sized>(widget: &mut W, rect: Rect) {
+    let bounds = widget.visual_bounds();
+    widget.shift(rect.x - bounds.x, rect.y - bounds.y);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn row_places_widgets_side_by_side() {
+        let rects = Layout::row().at(5, 5).spacing(2).rects(&[(10, 20), (30, 10)]);
+        assert_eq!(rects, vec![rect!(5, 5, 10, 20), rect!(17, 5, 30, 10)]);
+    }
+
+    #[test]
+    fn column_starts_after_padding() {
+        let rects = Layout::column().padding(4).spacing(1).rects(&[(10, 10), (10, 10)]);
+        assert_eq!(rects, vec![rect!(4, 4, 10, 10), rect!(4, 15, 10, 10)]);
+    }
+
+    #[test]
+    fn grid_aligns_widgets_in_their_cells() {
+        let rects = Layout::grid(2)
+            .align(Align::Center, Align::Center)
+            .rects(&[(10, 10), (20, 20), (10, 10)]);
+        assert_eq!(rects, vec![rect!(0, 5, 10, 10), rect!(10, 0, 20, 20), rect!(0, 20, 10, 10)]);
+    }
+
+    #[test]
+    fn anchor_offsets_point_away_from_the_edge() {
+        assert_eq!(Anchor::TopLeft.place((100, 100), (10, 10), (5, 5)), (5, 5));
+        assert_eq!(Anchor::BottomRight.place((100, 100), (10, 10), (5, 5)), (85, 85));
+        assert_eq!(Anchor::Center.place((100, 100), (10, 10), (0, 0)), (45, 45));
+        assert_eq!(Anchor::Top.place((100, 100), (10, 10), (0, 3)), (45, 3));
+    }
+}
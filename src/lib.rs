@@ -1,20 +1,31 @@
+mod callback;
+mod debug;
+mod error;
+mod font;
 mod handler;
+mod layout;
 mod gui;
 mod panel;
+mod path;
+mod simulate;
 pub mod widgets;
 
 pub use crate::gui::GUI;
+pub use crate::error::GuiError;
+pub use crate::font::{FontManager, FontSource, LoadedFont, SharedFont, TextTexture};
+pub use crate::layout::{Align, Anchor, Layout};
 pub use crate::panel::Panel;
+pub use crate::path::PanelPath;
+pub use crate::widgets::WidgetId;
 
 use sdl2::render::Canvas;
-use sdl2::ttf::Sdl2TtfContext;
 use sdl2::video::Window;
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 
-const FONT: &'static str = "./Courier_Prime.ttf";
+use std::borrow::Cow;
+
 const BACKROUNDCOLOR: Color = Color::RGB(40, 40, 40);
-static mut DEBUG: bool = false;
 
 #[macro_export]
 macro_rules! rect(
@@ -23,11 +34,21 @@ macro_rules! rect(
   )
 );
 
+/// Widget events report the path of the panel holding the widget and the widget's ID,
+/// which is its index unless it was given one with `.id(..)`
 pub enum GuiEvent<T> {
     Quit,
-    ButtonPress(&'static str, T),
-    FaderUpdate(&'static str, usize, f32),
-    DropdownUpdate(&'static str, usize, &'static str),
+    ButtonPress(PanelPath, WidgetId, T),
+    /// (panel, id, new value, callback of the fader)
+    FaderUpdate(PanelPath, WidgetId, f32, T),
+    /// (panel, id, label of the chosen option, its value)
+    DropdownUpdate(PanelPath, WidgetId, Cow<'static, str>, T),
+    /// Return was pressed in a writeable textfield: (panel, id, content, callback of the textfield)
+    TextSubmit(PanelPath, WidgetId, String, T),
+    /// Emitted by a user-defined widget: (panel, id, payload)
+    Custom(PanelPath, WidgetId, T),
+    /// The close button of a panel was clicked. The panel has been hidden.
+    PanelClose(Cow<'static, str>),
     None
 }
 
@@ -40,13 +61,21 @@ pub enum Direction {
 }
 
 pub trait Render {
-    fn render(&self, canvas: &mut Canvas<Window>) -> Result<(), String>;
+    fn render(&self, canvas: &mut Canvas<Window>) -> Result<(), GuiError>;
     fn render_text(
         &self,
-        _ttf: &Sdl2TtfContext,
+        _fonts: &FontManager,
+        _canvas: &mut Canvas<Window>,
+        _font: &FontSource,
+    ) -> Result<(), GuiError> { Ok(()) }
+    /// Draws popups such as open dropdown lists and tooltips. The overlay is drawn after all panels,
+    /// so it is never covered by another widget or panel.
+    fn render_overlay(
+        &self,
+        _fonts: &FontManager,
         _canvas: &mut Canvas<Window>,
-        _font: &'static str,
-    ) -> Result<(), String> { Ok(()) }
+        _font: &FontSource,
+    ) -> Result<(), GuiError> { Ok(()) }
 }
 
 fn bounding_box(rects: Vec<Rect>) -> Rect {
@@ -1,27 +1,52 @@
-use core::panic;
-
 use sdl2::pixels::Color;
 use sdl2::render::Canvas;
 use sdl2::video::Window;
-use sdl2::ttf::Sdl2TtfContext;
 use sdl2::rect::Rect;
 
+use std::borrow::Cow;
+
 use crate::Direction;
-use crate::widgets::{Button, DropdownButton, Fader, TextField, Widget, WidgetData, WidgetType};
-use crate::{bounding_box, in_bounds, GuiEvent, Render, DEBUG};
+use crate::widgets::{AnyWidget, Button, CustomWidget, DropdownButton, Fader, Orientation, TextCache, TextField, TextStyle, Widget, WidgetData, WidgetId, WidgetType};
+use crate::{bounding_box, in_bounds, rect, Anchor, FontManager, FontSource, GuiError, GuiEvent, PanelPath, Render};
+
+const TITLE_BAR_HEIGHT: u32 = 24;
+const TITLE_BAR_COLOR: Color = Color::RGB(50, 50, 60);
+const TITLE_COLOR: Color = Color::RGB(230, 230, 230);
+const CLOSE_BUTTON_MARGIN: i32 = 7;
+const SCROLLBAR_WIDTH: i32 = 8;
+const SCROLLBAR_TRACK_COLOR: Color = Color::RGB(60, 60, 66);
+const SCROLLBAR_THUMB_COLOR: Color = Color::RGB(130, 130, 140);
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Panel<T> 
 where
     T: Copy,
 {
-    pub name: &'static str,
+    pub name: Cow<'static, str>,
     pub bounds: Rect,
     pub buttons: Vec<Button<T>>,
-    pub textfields: Vec<TextField>,
-    pub faders: Vec<Fader>,
-    pub dropdownbuttons: Vec<DropdownButton>,
-    pub font: &'static str,
+    pub textfields: Vec<TextField<T>>,
+    pub faders: Vec<Fader<T>>,
+    pub dropdownbuttons: Vec<DropdownButton<T>>,
+    pub custom: Vec<Box<dyn CustomWidget<T>>>,
+    pub children: Vec<Panel<T>>,
+    pub font: Option<FontSource>,
+    position: (i32, i32),
+    origin: (i32, i32),
+    anchor: Anchor,
+    relative_sizes: Vec<(WidgetType, usize, Option<f32>, Option<f32>)>,
+    size: Option<(u32, u32)>,
+    background: Option<Color>,
+    border: Option<Color>,
+    padding: i32,
+    title: Option<Cow<'static, str>>,
+    draggable: bool,
+    closable: bool,
+    scrollable: bool,
+    scroll: (i32, i32),
+    // Offset of the widgets from the origin, made room for by padding and the title bar
+    content_offset: (i32, i32),
+    text_cache: TextCache,
     widget_order: Vec<(WidgetType, usize)>,
     active: Option<usize>
 }
@@ -31,98 +56,401 @@ where
     T: Copy + Default,
 {
     pub fn new(
-        name: &'static str,
+        name: impl Into<Cow<'static, str>>,
         position: (i32, i32),
         mut buttons: Vec<Button<T>>,
-        mut textfields: Vec<TextField>,
-        mut faders: Vec<Fader>,
-        mut dropdownbuttons: Vec<DropdownButton>
+        mut textfields: Vec<TextField<T>>,
+        mut faders: Vec<Fader<T>>,
+        mut dropdownbuttons: Vec<DropdownButton<T>>
     ) -> Panel<T> {
         buttons.iter_mut().for_each(|btn| btn.shift(position.0, position.1));
         textfields.iter_mut().for_each(|tf| tf.shift(position.0, position.1));
         faders.iter_mut().for_each(|fd| fd.shift(position.0, position.1));
         dropdownbuttons.iter_mut().for_each(|ddb| ddb.shift(position.0, position.1));
-        let bounds = bounding_box(
-            [
-                buttons.iter().map(|button| button.bounds()).collect::<Vec<Rect>>(),
-                textfields.iter().map(|textfield| textfield.visual_bounds()).collect::<Vec<Rect>>(),
-                faders.iter().map(|fader| fader.bounds()).collect::<Vec<Rect>>(),
-                dropdownbuttons.iter().map(|ddb| ddb.bounds()).collect::<Vec<Rect>>()
-            ].concat()
-        );
-        let order = widget_order(buttons.clone(), textfields.clone(), faders.clone(), dropdownbuttons.clone());
-        Panel { 
-            name, 
-            bounds, 
+        let mut panel = Panel { 
+            name: name.into(), 
+            bounds: Rect::new(0, 0, 0, 0), 
             buttons, 
             textfields, 
             faders,
             dropdownbuttons, 
-            font: crate::FONT, 
-            widget_order: order,
+            custom: vec![],
+            children: vec![],
+            font: None,
+            position,
+            origin: position,
+            anchor: Anchor::TopLeft,
+            relative_sizes: vec![],
+            size: None,
+            background: None,
+            border: None,
+            padding: 0,
+            title: None,
+            draggable: false,
+            closable: false,
+            scrollable: false,
+            scroll: (0, 0),
+            content_offset: (0, 0),
+            text_cache: TextCache::new(),
+            widget_order: vec![],
             active: None
+        };
+        panel.update_layout();
+        panel
+    }
+
+    /// Adds a user-defined widget, positioned relative to the panel like the built-in ones
+    pub fn custom_widget<W>(mut self, mut widget: W) -> Panel<T>
+    where
+        W: CustomWidget<T> + 'static,
+    {
+        widget.shift(self.origin.0 + self.content_offset.0, self.origin.1 + self.content_offset.1);
+        self.custom.push(Box::new(widget));
+        self.update_layout();
+        self
+    }
+
+    /// Nests a panel inside this one, positioned relative to this panel's content area like a widget.
+    /// Child panels are drawn and hit-tested above the widgets of their parent, and their widgets
+    /// follow the parent's in focus order. Panel names must be unique within a GUI.
+    /// `.draggable()` and `.closable()` have no effect on a child panel.
+    pub fn child(mut self, mut child: Panel<T>) -> Panel<T> {
+        // Only top-level panels can be moved and hidden, so a child keeps just its caption
+        if child.draggable || child.closable {
+            child.draggable = false;
+            child.closable = false;
+            if child.title.as_deref() == Some("") {
+                child.title = None;
+            }
+            child.update_content_offset();
         }
+        child.move_by(self.origin.0 + self.content_offset.0, self.origin.1 + self.content_offset.1);
+        self.children.push(child);
+        self.update_layout();
+        self
     }
 
-    pub fn draw(&self, canvas: &mut Canvas<Window>, ttf: &Sdl2TtfContext)
-    -> Result<(), String> {
-        // Widgets need to render from last to first in order for dropdown buttons to render correctly on top of each other
-        for idx in 1..=self.widget_order.len() {
-            let idx = self.widget_order.len()-idx;
-            match self.widget_order[idx].0 {
-                WidgetType::Button => {
-                    self.buttons[self.widget_order[idx].1].render(canvas)?;
-                    self.buttons[self.widget_order[idx].1].render_text(ttf, canvas, self.font)?;
-                },
-                WidgetType::Fader => {
-                    self.faders[self.widget_order[idx].1].render(canvas)?;
-                    self.faders[self.widget_order[idx].1].render_text(ttf, canvas, self.font)?;
-                },
-                WidgetType::DropdownButton => {
-                    self.dropdownbuttons[self.widget_order[idx].1].render(canvas)?;
-                    self.dropdownbuttons[self.widget_order[idx].1].render_text(ttf, canvas, self.font)?;
-                },
-                WidgetType::TextField => {
-                    self.textfields[self.widget_order[idx].1].render(canvas)?;
-                    self.textfields[self.widget_order[idx].1].render_text(ttf, canvas, self.font)?;
-                }
+    /// Positions the panel relative to an edge or corner of the window.
+    /// The position given to `Panel::new` becomes the distance from that edge.
+    pub fn anchor(mut self, anchor: Anchor) -> Panel<T> {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Sizes a widget as a percentage of the window's width and/or height
+    pub fn relative_size(mut self, w_type: WidgetType, id: impl Into<WidgetId>, width: Option<f32>, height: Option<f32>) -> Result<Panel<T>, GuiError> {
+        let idx = self.index_of(w_type, id)?;
+        self.relative_sizes.push((w_type, idx, width, height));
+        Ok(self)
+    }
+
+    /// Gives the panel a fixed size instead of fitting it around its widgets
+    pub fn size(mut self, w: u32, h: u32) -> Panel<T> {
+        self.size = Some((w, h));
+        self.update_layout();
+        self
+    }
+
+    pub fn background_rgb(mut self, r: u8, g: u8, b: u8) -> Panel<T> {
+        self.background = Some(Color::RGB(r, g, b));
+        self.update_layout();
+        self
+    }
+
+    pub fn border_rgb(mut self, r: u8, g: u8, b: u8) -> Panel<T> {
+        self.border = Some(Color::RGB(r, g, b));
+        self.update_layout();
+        self
+    }
+
+    /// Space between the edges of the panel and its widgets
+    pub fn padding(mut self, padding: u32) -> Panel<T> {
+        self.padding = padding as i32;
+        self.update_content_offset();
+        self
+    }
+
+    /// Adds a title bar with a caption above the widgets
+    pub fn title(mut self, title: impl Into<Cow<'static, str>>) -> Panel<T> {
+        self.title = Some(title.into());
+        self.update_content_offset();
+        self
+    }
+
+    /// Lets the user move the panel by dragging its title bar. Adds an empty title bar if there is none.
+    /// Draggable panels are raised above the others when clicked.
+    pub fn draggable(mut self) -> Panel<T> {
+        self.draggable = true;
+        self.title.get_or_insert(Cow::Borrowed(""));
+        self.update_content_offset();
+        self
+    }
+
+    /// Adds a close button to the title bar, which hides the panel and emits `GuiEvent::PanelClose`.
+    /// Adds an empty title bar if there is none.
+    pub fn closable(mut self) -> Panel<T> {
+        self.closable = true;
+        self.title.get_or_insert(Cow::Borrowed(""));
+        self.update_content_offset();
+        self
+    }
+
+    /// Changes the caption of the title bar, adding one if there is none
+    pub fn set_title(&mut self, title: impl Into<Cow<'static, str>>) {
+        self.title = Some(title.into());
+        self.update_content_offset();
+    }
+
+    pub fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub const fn is_draggable(&self) -> bool {
+        self.draggable
+    }
+
+    /// Lets the user scroll through widgets that don't fit into the panel's `size`,
+    /// with the mouse wheel or by dragging the scrollbars. Widgets are clipped to the panel.
+    pub fn scrollable(mut self) -> Panel<T> {
+        self.scrollable = true;
+        self
+    }
+
+    pub const fn is_scrollable(&self) -> bool {
+        self.scrollable
+    }
+
+    /// How far the content of the panel is scrolled to the right and down
+    pub const fn scroll_offset(&self) -> (i32, i32) {
+        self.scroll
+    }
+
+    /// Scrolls to the given offset, clamped to the size of the content
+    pub fn scroll_to(&mut self, x: i32, y: i32) {
+        let max = self.max_scroll();
+        let scroll = (x.clamp(0, max.0), y.clamp(0, max.1));
+        self.shift_widgets(self.scroll.0 - scroll.0, self.scroll.1 - scroll.1);
+        self.scroll = scroll;
+        self.update_layout();
+    }
+
+    pub fn scroll_by(&mut self, x: i32, y: i32) {
+        self.scroll_to(self.scroll.0 + x, self.scroll.1 + y);
+    }
+
+    /// Overrides the font of the GUI for this panel
+    pub fn font(mut self, font: impl Into<FontSource>) -> Panel<T> {
+        self.font = Some(font.into());
+        self
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>, fonts: &FontManager, default_font: &FontSource)
+    -> Result<(), GuiError> {
+        let font = self.font.as_ref().unwrap_or(default_font);
+        self.draw_chrome(canvas, fonts, font)?;
+        let clip = canvas.clip_rect();
+        if self.scrollable {
+            let viewport = self.viewport();
+            match clip.map_or(Some(viewport), |clip| clip.intersection(viewport)) {
+                Some(visible) => canvas.set_clip_rect(Some(visible)),
+                // Scrolled out of view of an ancestor, and no clip would draw everything
+                None => return Ok(()),
             }
         }
-        if unsafe { DEBUG } {
-            canvas.set_draw_color(Color::RGB(255, 0, 0));
-            canvas.draw_rect(self.bounds)?;
+        for &(w_type, idx) in self.widget_order.iter() {
+            let widget = self.renderable(w_type, idx);
+            widget.render(canvas)?;
+            widget.render_text(fonts, canvas, font)?;
         }
-
         if let Some(widget) = self.active {
-            let widget = self.widget_order[widget];
-            let rect = match widget.0 {
-                WidgetType::Button => {
-                    self.buttons[widget.1].visual_bounds()
-                },
-                WidgetType::Fader => {
-                    self.faders[widget.1].visual_bounds()
-                },
-                WidgetType::TextField => {
-                    self.textfields[widget.1].bounds()
-                },
-                WidgetType::DropdownButton => {
-                    self.dropdownbuttons[widget.1].bounds()
-                }
+            let (w_type, idx) = self.widget_order[widget];
+            let rect = match w_type {
+                WidgetType::TextField | WidgetType::DropdownButton => self.widget(w_type, idx).bounds(),
+                _ => self.widget(w_type, idx).visual_bounds(),
             };
             canvas.set_draw_color(Color::RGB(80, 80, 180));
             canvas.draw_rect(rect)?;
         }
+        for child in self.children.iter() {
+            child.draw(canvas, fonts, font)?;
+        }
+        canvas.set_clip_rect(clip);
+        for orientation in [Orientation::Vertical, Orientation::Horizontal] {
+            if let Some((track, thumb)) = self.scrollbar(orientation) {
+                canvas.set_draw_color(SCROLLBAR_TRACK_COLOR);
+                canvas.fill_rect(track)?;
+                canvas.set_draw_color(SCROLLBAR_THUMB_COLOR);
+                canvas.fill_rect(thumb)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws the popups of the panel's widgets. Called after every panel has been drawn.
+    pub fn draw_overlay(&self, canvas: &mut Canvas<Window>, fonts: &FontManager, default_font: &FontSource)
+    -> Result<(), GuiError> {
+        let font = self.font.as_ref().unwrap_or(default_font);
+        for &(w_type, idx) in self.widget_order.iter() {
+            self.renderable(w_type, idx).render_overlay(fonts, canvas, font)?;
+        }
+        for child in self.children.iter() {
+            child.draw_overlay(canvas, fonts, font)?;
+        }
         Ok(())
     }
 
-    pub fn get_input(&self, idx: usize) -> String {
-        if idx >= self.textfields.len() {
-            panic!("get_input: Invalid textfield index")
+    /// Adds a widget after the panel was built, positioned relative to the panel like the widgets
+    /// passed to `Panel::new`. Returns the type and index the widget is addressed by.
+    /// Use `GUI::add_widget` for panels in a GUI, which also updates the panels around it.
+    pub fn add_widget(&mut self, widget: impl Into<AnyWidget<T>>) -> (WidgetType, usize) {
+        let mut widget = widget.into();
+        let (x, y) = self.widget_offset();
+        widget.shift(x, y);
+        let added = match widget {
+            AnyWidget::Button(button) => {
+                self.buttons.push(button);
+                (WidgetType::Button, self.buttons.len() - 1)
+            },
+            AnyWidget::TextField(textfield) => {
+                self.textfields.push(textfield);
+                (WidgetType::TextField, self.textfields.len() - 1)
+            },
+            AnyWidget::Fader(fader) => {
+                self.faders.push(fader);
+                (WidgetType::Fader, self.faders.len() - 1)
+            },
+            AnyWidget::DropdownButton(dropdown) => {
+                self.dropdownbuttons.push(dropdown);
+                (WidgetType::DropdownButton, self.dropdownbuttons.len() - 1)
+            },
+            AnyWidget::Custom(custom) => {
+                self.custom.push(custom);
+                (WidgetType::Custom, self.custom.len() - 1)
+            },
+        };
+        self.update_layout();
+        added
+    }
+
+    /// Removes a widget and returns it, positioned relative to the panel again.
+    /// Later widgets of the same type move down one index.
+    /// Use `GUI::remove_widget` for panels in a GUI, which also updates hover and focus.
+    pub fn remove_widget(&mut self, w_type: WidgetType, id: impl Into<WidgetId>) -> Result<AnyWidget<T>, GuiError> {
+        let idx = self.index_of(w_type, id)?;
+        if self.active.is_some_and(|active| self.widget_order[active] == (w_type, idx)) {
+            self.deselect_active();
+        }
+        let mut widget = match w_type {
+            WidgetType::Button => AnyWidget::Button(self.buttons.remove(idx)),
+            WidgetType::TextField => AnyWidget::TextField(self.textfields.remove(idx)),
+            WidgetType::Fader => AnyWidget::Fader(self.faders.remove(idx)),
+            WidgetType::DropdownButton => AnyWidget::DropdownButton(self.dropdownbuttons.remove(idx)),
+            WidgetType::Custom => AnyWidget::Custom(self.custom.remove(idx)),
         };
-        self.textfields[idx].to_string()
+        let (x, y) = self.widget_offset();
+        widget.shift(-x, -y);
+
+        let focused = self.active.and_then(|active| reindex(self.widget_order[active], (w_type, idx)));
+        self.widget_order = self.widget_order
+            .iter()
+            .filter_map(|&widget| reindex(widget, (w_type, idx)))
+            .collect();
+        self.active = focused.and_then(|focused| self.widget_order.iter().position(|&widget| widget == focused));
+        self.relative_sizes = self.relative_sizes
+            .iter()
+            .filter_map(|&(ty, i, width, height)| reindex((ty, i), (w_type, idx)).map(|(ty, i)| (ty, i, width, height)))
+            .collect();
+        self.update_layout();
+        // The content may have shrunk below the scroll offset
+        self.scroll_by(0, 0);
+        Ok(widget)
+    }
+
+    /// Index of the widget among the widgets of its type, looked up by ID or checked to exist
+    pub fn index_of(&self, w_type: WidgetType, id: impl Into<WidgetId>) -> Result<usize, GuiError> {
+        let id = id.into();
+        let count = self.widget_count(w_type);
+        let idx = match id {
+            WidgetId::Index(idx) => (idx < count).then_some(idx),
+            WidgetId::Name(ref name) => (0..count).find(|&idx| self.widget(w_type, idx).get_id().as_ref() == Some(name)),
+        };
+        idx.ok_or_else(|| self.not_found(w_type, id))
+    }
+
+    /// The ID reported in events for a widget: its user-assigned ID, or its index
+    pub(crate) fn widget_id(&self, w_type: WidgetType, idx: usize) -> WidgetId {
+        self.widget(w_type, idx)
+            .get_id()
+            .map_or(WidgetId::Index(idx), WidgetId::Name)
+    }
+
+    pub fn get_input(&self, id: impl Into<WidgetId>) -> Result<String, GuiError> {
+        Ok(self.textfield(id)?.to_string())
     }
 
-    pub fn arrow_key(&mut self, w_type: WidgetType, idx: usize, dir: Direction) -> Option<GuiEvent<T>> {
+    pub fn textfield(&self, id: impl Into<WidgetId>) -> Result<&TextField<T>, GuiError> {
+        Ok(&self.textfields[self.index_of(WidgetType::TextField, id)?])
+    }
+
+    pub fn textfield_mut(&mut self, id: impl Into<WidgetId>) -> Result<&mut TextField<T>, GuiError> {
+        let idx = self.index_of(WidgetType::TextField, id)?;
+        Ok(&mut self.textfields[idx])
+    }
+
+    pub fn fader(&self, id: impl Into<WidgetId>) -> Result<&Fader<T>, GuiError> {
+        Ok(&self.faders[self.index_of(WidgetType::Fader, id)?])
+    }
+
+    pub fn fader_mut(&mut self, id: impl Into<WidgetId>) -> Result<&mut Fader<T>, GuiError> {
+        let idx = self.index_of(WidgetType::Fader, id)?;
+        Ok(&mut self.faders[idx])
+    }
+
+    pub fn dropdownbutton(&self, id: impl Into<WidgetId>) -> Result<&DropdownButton<T>, GuiError> {
+        Ok(&self.dropdownbuttons[self.index_of(WidgetType::DropdownButton, id)?])
+    }
+
+    pub fn dropdownbutton_mut(&mut self, id: impl Into<WidgetId>) -> Result<&mut DropdownButton<T>, GuiError> {
+        let idx = self.index_of(WidgetType::DropdownButton, id)?;
+        Ok(&mut self.dropdownbuttons[idx])
+    }
+
+    pub fn button(&self, id: impl Into<WidgetId>) -> Result<&Button<T>, GuiError> {
+        Ok(&self.buttons[self.index_of(WidgetType::Button, id)?])
+    }
+
+    pub fn button_mut(&mut self, id: impl Into<WidgetId>) -> Result<&mut Button<T>, GuiError> {
+        let idx = self.index_of(WidgetType::Button, id)?;
+        Ok(&mut self.buttons[idx])
+    }
+
+    pub fn set_button_label(&mut self, id: impl Into<WidgetId>, label: impl Into<Cow<'static, str>>) -> Result<(), GuiError> {
+        self.button_mut(id)?.set_label(label);
+        Ok(())
+    }
+
+    /// Changes the label of a textfield. Adding or removing a label changes the textfield's visual bounds.
+    pub fn set_textfield_label(&mut self, id: impl Into<WidgetId>, label: impl Into<Cow<'static, str>>) -> Result<(), GuiError> {
+        self.textfield_mut(id)?.set_label(label);
+        self.update_layout();
+        Ok(())
+    }
+
+    pub fn set_dropdown_label(&mut self, id: impl Into<WidgetId>, label: impl Into<Cow<'static, str>>) -> Result<(), GuiError> {
+        self.dropdownbutton_mut(id)?.set_label(label);
+        Ok(())
+    }
+
+    pub fn set_dropdown_options<S>(&mut self, id: impl Into<WidgetId>, options: impl IntoIterator<Item = (S, T)>) -> Result<(), GuiError>
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        self.dropdownbutton_mut(id)?.set_options(options);
+        Ok(())
+    }
+
+    pub(crate) fn arrow_key(&mut self, widget: &WidgetData, dir: Direction) -> Option<GuiEvent<T>> {
+        let (ref path, w_type, idx) = *widget;
         match w_type {
             WidgetType::Fader => {
                 match dir {
@@ -130,146 +458,358 @@ where
                     Direction::Down | Direction::Left => self.faders[idx].decrement()
                 }
                 Some(GuiEvent::FaderUpdate(
-                    self.name, 
-                    idx, 
-                    self.faders[idx].value()
+                    path.clone(), 
+                    self.widget_id(w_type, idx), 
+                    self.faders[idx].value(),
+                    self.faders[idx].get_callback()
                 ))
             },
+            WidgetType::Custom => self.custom[idx]
+                .arrow_key(dir)
+                .map(|payload| GuiEvent::Custom(path.clone(), self.widget_id(w_type, idx), payload)),
             _ => None
         }
     }
 
-    pub fn deselect(&mut self, w_type: WidgetType, idx: usize) {
+    pub(crate) fn deselect(&mut self, w_type: WidgetType, idx: usize) {
         match w_type {
             WidgetType::TextField => self.textfields[idx].set_active(false),
             WidgetType::DropdownButton => self.dropdownbuttons[idx].close(),
+            WidgetType::Custom => self.custom[idx].set_focused(false),
             _ => {}
         }
         self.active = None;
     }
 
-    pub fn next_widget(&mut self) -> WidgetData {
-        if self.active.is_none() {
-            self.active = Some(0);
-            self.select_active();
-            return (self.name, self.widget_order[0].0, self.widget_order[0].1);
-        }
-        // Should only go to next if current is not dropdown that is at the end
-        let active_index = self.active_widget_index().unwrap();
-        if self.active_widget_type() == Some(WidgetType::DropdownButton) 
-        && self.dropdownbuttons[active_index].next() {
-            (self.name, self.widget_order[active_index].0, self.widget_order[active_index].1)
-        } else {
-            let new = (self.active.unwrap() + 1) % self.widget_order.len();
-            
-            self.deselect_active();
-            self.active = Some(new);
-            self.select_active();
-            
-            (self.name, self.widget_order[new].0, self.widget_order[new].1)
+    /// Moves focus to the next widget of the panel and its descendants, wrapping around at the end
+    pub(crate) fn next_widget(&mut self) -> Option<WidgetData> {
+        let order = self.focus_order();
+        if order.is_empty() {
+            return None;
         }
-        //  */
+        let focused = self.focused();
+        let new = match focused.as_ref().and_then(|focused| order.iter().position(|widget| widget == focused)) {
+            None => 0,
+            Some(position) => {
+                // Open dropdowns step through their options before passing focus on
+                let (path, w_type, idx) = &order[position];
+                if *w_type == WidgetType::DropdownButton
+                && self.descendant_mut(path).is_some_and(|panel| panel.dropdownbuttons[*idx].next()) {
+                    return Some(order[position].clone());
+                }
+                (position + 1) % order.len()
+            }
+        };
+        self.move_focus(focused, &order[new]);
+        Some(order[new].clone())
     }
 
-    pub fn previous_widget(&mut self) -> Option<WidgetData> {
-        let new = match self.active {
-            Some(0) => self.widget_order.len()-1,
-            Some(n) => n-1,
-            None => return None
+    pub(crate) fn previous_widget(&mut self) -> Option<WidgetData> {
+        let order = self.focus_order();
+        let focused = self.focused()?;
+        let new = match order.iter().position(|widget| *widget == focused)? {
+            0 => order.len() - 1,
+            n => n - 1,
         };
-        
-        self.deselect_active();
-        self.active = Some(new);
-        self.select_active();
+        self.move_focus(Some(focused), &order[new]);
+        Some(order[new].clone())
+    }
 
-        Some((self.name, self.widget_order[new].0, self.widget_order[new].1))
-    } 
+    /// Widgets of the panel and its descendants in focus order: the panel's own widgets, then each child's
+    fn focus_order(&self) -> Vec<WidgetData> {
+        let mut order = vec![];
+        self.collect_focus_order(PanelPath::new(self.name.clone()), &mut order);
+        order
+    }
 
-    pub fn set_textfield_content(&mut self, idx: usize, content: String) {
-        if idx >= self.textfields.len() {
-            return;
-        };
-        if let Some(textfield) = self.textfields.iter_mut().nth(idx) {
-            textfield.set_content(content);
+    fn collect_focus_order(&self, path: PanelPath, order: &mut Vec<WidgetData>) {
+        order.extend(self.widget_order.iter().map(|&(w_type, idx)| (path.clone(), w_type, idx)));
+        for child in self.children.iter() {
+            child.collect_focus_order(path.child(child.name.clone()), order);
         }
     }
 
-    pub fn push_to_textfield(&mut self, idx: usize, c: char) {
-        self.textfields[idx].push(c.to_string());
+    fn focused(&self) -> Option<WidgetData> {
+        self.find_focused(PanelPath::new(self.name.clone()))
     }
 
-    pub fn push_to_active_textfields(&mut self, s: &str) {
+    fn find_focused(&self, path: PanelPath) -> Option<WidgetData> {
+        if let Some(active) = self.active {
+            let (w_type, idx) = self.widget_order[active];
+            return Some((path, w_type, idx));
+        }
+        self.children
+            .iter()
+            .find_map(|child| child.find_focused(path.child(child.name.clone())))
+    }
+
+    fn move_focus(&mut self, from: Option<WidgetData>, to: &WidgetData) {
+        if let Some(panel) = from.and_then(|from| self.descendant_mut(&from.0)) {
+            panel.deselect_active();
+            panel.active = None;
+        }
+        if let Some(panel) = self.descendant_mut(&to.0) {
+            panel.active = panel.widget_order.iter().position(|&widget| widget == (to.1, to.2));
+            panel.select_active();
+        }
+        // Scrollable ancestors follow, innermost first, since scrolling one moves the widget
+        let mut ancestor = to.0.parent();
+        while let Some(path) = ancestor {
+            let rect = self.descendant(&to.0).map(|panel| panel.widget(to.1, to.2).visual_bounds());
+            if let (Some(rect), Some(panel)) = (rect, self.descendant_mut(&path)) {
+                panel.scroll_rect_into_view(rect);
+            }
+            ancestor = path.parent();
+        }
+    }
+
+    /// The panel or one of its descendants at `path`, which starts with this panel's name
+    pub(crate) fn descendant(&self, path: &PanelPath) -> Option<&Panel<T>> {
+        let (root, rest) = path.panels().split_first()?;
+        if *root != self.name {
+            return None;
+        }
+        rest.iter().try_fold(self, |panel, name| panel.children.iter().find(|child| child.name == *name))
+    }
+
+    pub(crate) fn descendant_mut(&mut self, path: &PanelPath) -> Option<&mut Panel<T>> {
+        let (root, rest) = path.panels().split_first()?;
+        if *root != self.name {
+            return None;
+        }
+        rest.iter().try_fold(self, |panel, name| panel.children.iter_mut().find(|child| child.name == *name))
+    }
+
+    /// Path from this panel to the panel or descendant named `name`
+    pub(crate) fn path_to(&self, name: &str) -> Option<PanelPath> {
+        self.find_path(PanelPath::new(self.name.clone()), name)
+    }
+
+    fn find_path(&self, path: PanelPath, name: &str) -> Option<PanelPath> {
+        if self.name == name {
+            return Some(path);
+        }
+        self.children
+            .iter()
+            .find_map(|child| child.find_path(path.child(child.name.clone()), name))
+    }
+
+    /// Removes the descendant named `name` from the tree below this panel
+    pub(crate) fn remove_descendant(&mut self, name: &str) -> Option<Panel<T>> {
+        if let Some(index) = self.children.iter().position(|child| child.name == name) {
+            let child = self.children.remove(index);
+            self.update_layout();
+            return Some(child);
+        }
+        let removed = self.children.iter_mut().find_map(|child| child.remove_descendant(name));
+        if removed.is_some() {
+            self.update_layout();
+        }
+        removed
+    }
+
+    /// The panel or one of its descendants named `name`
+    pub(crate) fn find(&self, name: &str) -> Option<&Panel<T>> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(name))
+    }
+
+    pub(crate) fn find_mut(&mut self, name: &str) -> Option<&mut Panel<T>> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter_mut().find_map(|child| child.find_mut(name))
+    }
+
+    /// The panel followed by all of its descendants
+    pub(crate) fn descendants(&self) -> Vec<&Panel<T>> {
+        let mut panels = vec![self];
+        for child in self.children.iter() {
+            panels.extend(child.descendants());
+        }
+        panels
+    }
+
+    pub fn set_textfield_content(&mut self, id: impl Into<WidgetId>, content: String) -> Result<(), GuiError> {
+        self.textfield_mut(id)?.set_content(content);
+        Ok(())
+    }
+
+    pub fn push_to_textfield(&mut self, id: impl Into<WidgetId>, c: char) -> Result<(), GuiError> {
+        self.textfield_mut(id)?.push(c.to_string());
+        Ok(())
+    }
+
+    pub(crate) fn push_to_active_textfields(&mut self, s: &str) {
         self.textfields.iter_mut().for_each(|textfield| {
             if textfield.is_active() {
                 textfield.push(s.to_string());
             }
         });
+        self.children.iter_mut().for_each(|child| child.push_to_active_textfields(s));
+    }
+
+    pub(crate) fn pop_from_active_textfields(&mut self) {
+        self.textfields.iter_mut().for_each(|textfield| {
+            if textfield.is_active() {
+                textfield.pop_char();
+            }
+        });
+        self.children.iter_mut().for_each(|child| child.pop_from_active_textfields());
     }
 
-    pub fn pop_from_textfield(&mut self, idx: usize) -> Option<char> {
-        self.textfields[idx].pop_char()
+    pub fn pop_from_textfield(&mut self, id: impl Into<WidgetId>) -> Result<Option<char>, GuiError> {
+        Ok(self.textfield_mut(id)?.pop_char())
+    }
+
+    pub fn clear_textfield(&mut self, id: impl Into<WidgetId>) -> Result<(), GuiError> {
+        self.textfield_mut(id)?.clear();
+        Ok(())
     }
 
-    pub fn clear_textfield(&mut self, idx: usize) {
-        self.textfields[idx].clear();
+    pub fn set_fader_value(&mut self, id: impl Into<WidgetId>, value: f32) -> Result<(), GuiError> {
+        self.fader_mut(id)?.set_fader_value(value)
     }
 
-    pub fn unhover(&mut self, w_type: WidgetType, idx: usize) {
+    pub(crate) fn unhover(&mut self, w_type: WidgetType, idx: usize) {
         match w_type {
             WidgetType::Button => self.buttons[idx].is_hovered(false),
             WidgetType::Fader => self.faders[idx].is_hovered(false),
             WidgetType::DropdownButton => self.dropdownbuttons[idx].unhover(),
+            WidgetType::Custom => self.custom[idx].set_hovered(false),
             _ => {}
         }    
     }
 
-    pub fn hover(&mut self, w_type: WidgetType, idx: usize) {
+    pub(crate) fn hover(&mut self, w_type: WidgetType, idx: usize) {
         match w_type {
             WidgetType::Button => self.buttons[idx].is_hovered(true),
             WidgetType::Fader => self.faders[idx].is_hovered(true),
+            WidgetType::Custom => self.custom[idx].set_hovered(true),
             _ => {}
         };
     }
 
-    pub fn get_widget_data(&self, x: i32, y: i32) -> Option<WidgetData> {
+    /// The widget of the panel or its descendants whose overlay is at (x, y)
+    pub(crate) fn overlay_at(&self, x: i32, y: i32) -> Option<WidgetData> {
+        self.find_overlay(PanelPath::new(self.name.clone()), x, y)
+    }
+
+    fn find_overlay(&self, path: PanelPath, x: i32, y: i32) -> Option<WidgetData> {
+        if let Some(widget) = self.children
+            .iter()
+            .rev()
+            .find_map(|child| child.find_overlay(path.child(child.name.clone()), x, y)) {
+            return Some(widget);
+        }
+        self.widget_order
+            .iter()
+            .find(|&&(w_type, idx)| self
+                .widget(w_type, idx)
+                .overlay_bounds()
+                .is_some_and(|overlay| in_bounds(&overlay, x, y))
+            )
+            .map(|&(w_type, idx)| (path, w_type, idx))
+    }
+
+    /// The widget at (x, y). Overlays come first, then child panels, then the panel's own widgets.
+    pub(crate) fn get_widget_data(&self, x: i32, y: i32) -> Option<WidgetData> {
+        self.overlay_at(x, y)
+            .or_else(|| self.find_widget(PanelPath::new(self.name.clone()), x, y))
+    }
+
+    fn find_widget(&self, path: PanelPath, x: i32, y: i32) -> Option<WidgetData> {
+        // Widgets scrolled out of view can't be hit
+        if self.scrollable && !in_bounds(&self.viewport(), x, y) {
+            return None;
+        }
+        if let Some(widget) = self.children
+            .iter()
+            .rev()
+            .find_map(|child| child.find_widget(path.child(child.name.clone()), x, y)) {
+            return Some(widget);
+        }
         if let Some(btn) = self.buttons
             .iter()
             .enumerate()
             .find(|btn| in_bounds(&btn.1.visual_bounds(), x, y)) {
-            return Some((self.name, WidgetType::Button, btn.0));
+            return Some((path, WidgetType::Button, btn.0));
         }
         if let Some(tf) = self.textfields
             .iter()
             .enumerate()
             .find(|tf| in_bounds(&tf.1.visual_bounds(), x, y)) {
-            return Some((self.name, WidgetType::TextField, tf.0));
+            return Some((path, WidgetType::TextField, tf.0));
         }
         if let Some(fd) = self.faders
             .iter()
             .enumerate()
             .find(|fd| in_bounds(&fd.1.visual_bounds(), x, y)) {
-            return Some((self.name, WidgetType::Fader, fd.0));
+            return Some((path, WidgetType::Fader, fd.0));
         }
         if let Some(ddb) = self.dropdownbuttons
             .iter()
             .enumerate()
             .find(|ddb| in_bounds(&ddb.1.visual_bounds(), x, y)) {
-            return Some((self.name, WidgetType::DropdownButton, ddb.0));
+            return Some((path, WidgetType::DropdownButton, ddb.0));
+        }
+        if let Some(custom) = self.custom
+            .iter()
+            .enumerate()
+            .find(|custom| in_bounds(&custom.1.visual_bounds(), x, y)) {
+            return Some((path, WidgetType::Custom, custom.0));
         }
         None
     }
 
-    pub fn drag(&mut self, w_type: WidgetType, idx: usize, x: i32, y: i32) -> Option<f32> {
+    /// The innermost of the panel and its descendants under (x, y) for which `f` returns something
+    fn innermost_at<R, F>(&self, path: PanelPath, x: i32, y: i32, f: &F) -> Option<(PanelPath, R)>
+    where
+        F: Fn(&Panel<T>) -> Option<R>,
+    {
+        if !in_bounds(&self.bounds, x, y) {
+            return None;
+        }
+        if !self.scrollable || in_bounds(&self.viewport(), x, y) {
+            if let Some(found) = self.children
+                .iter()
+                .rev()
+                .find_map(|child| child.innermost_at(path.child(child.name.clone()), x, y, f)) {
+                return Some(found);
+            }
+        }
+        f(self).map(|result| (path, result))
+    }
+
+    /// The innermost scrollable panel under (x, y)
+    pub(crate) fn scrollable_at(&self, x: i32, y: i32) -> Option<PanelPath> {
+        self.innermost_at(PanelPath::new(self.name.clone()), x, y, &|panel: &Panel<T>| panel.scrollable.then_some(()))
+            .map(|(path, _)| path)
+    }
+
+    /// The innermost panel with a scrollbar at (x, y)
+    pub(crate) fn scrollbar_at(&self, x: i32, y: i32) -> Option<(PanelPath, Orientation)> {
+        self.innermost_at(PanelPath::new(self.name.clone()), x, y, &|panel: &Panel<T>| panel.scrollbar_under(x, y))
+    }
+
+    pub(crate) fn drag(&mut self, widget: &WidgetData, x: i32, y: i32) -> Option<GuiEvent<T>> {
+        let (ref path, w_type, idx) = *widget;
         match w_type {
             WidgetType::Fader => {
                 self.faders[idx].drag(x, y);
-                Some(self.faders[idx].value())
+                let fader = &self.faders[idx];
+                Some(GuiEvent::FaderUpdate(path.clone(), self.widget_id(w_type, idx), fader.value(), fader.get_callback()))
             }
+            WidgetType::Custom => self.custom[idx]
+                .drag(x, y)
+                .map(|payload| GuiEvent::Custom(path.clone(), self.widget_id(w_type, idx), payload)),
             _ => None
         }
     }
 
-    pub fn click(&mut self, widget: WidgetData) -> Option<GuiEvent<T>> {
+    pub(crate) fn click(&mut self, widget: &WidgetData) -> Option<GuiEvent<T>> {
         if self.active_widget_type() != Some(WidgetType::DropdownButton) 
         || widget.1 != WidgetType::DropdownButton {
             self.deselect_active();
@@ -281,8 +821,9 @@ where
             .unwrap()
             .0
         );
+        let id = self.widget_id(widget.1, widget.2);
         match widget.1 {
-            WidgetType::Button => Some(GuiEvent::ButtonPress(self.name, self.buttons[widget.2].click())),
+            WidgetType::Button => Some(GuiEvent::ButtonPress(widget.0.clone(), id, self.buttons[widget.2].click())),
             WidgetType::TextField => {
                 if self.textfields[widget.2].is_writeable() {
                     self.textfields[widget.2].set_active(true);
@@ -290,26 +831,374 @@ where
                 None
             },
             WidgetType::DropdownButton => {
-                println!("Clicking dropdownbutton: {:?}", widget);
-                if let Some(str) = self.dropdownbuttons[widget.2].click() {
-                    return Some(GuiEvent::DropdownUpdate(self.name, widget.2, str))
+                if let Some((label, value)) = self.dropdownbuttons[widget.2].click() {
+                    return Some(GuiEvent::DropdownUpdate(widget.0.clone(), id, label, value))
                 }
                 None
             }
-            WidgetType::Fader => None
+            WidgetType::Fader => None,
+            WidgetType::Custom => self.custom[widget.2]
+                .click()
+                .map(|payload| GuiEvent::Custom(widget.0.clone(), id, payload)),
         }
     }
 
-    pub fn hover_dropdown(&mut self, idx: usize, x: i32, y: i32) {
+    /// Return on a focused textfield submits its content, on any other widget it clicks the widget
+    pub(crate) fn submit(&mut self, widget: &WidgetData) -> Option<GuiEvent<T>> {
+        let (ref path, w_type, idx) = *widget;
+        match w_type {
+            WidgetType::TextField if self.textfields[idx].is_writeable() => Some(GuiEvent::TextSubmit(
+                path.clone(),
+                self.widget_id(w_type, idx),
+                self.textfields[idx].to_string(),
+                self.textfields[idx].get_callback(),
+            )),
+            _ => self.click(widget),
+        }
+    }
+
+    /// Widgets in focus order as (type, index, bounds, visual bounds)
+    pub(crate) fn widget_rects(&self) -> Vec<(WidgetType, usize, Rect, Rect)> {
+        self.widget_order
+            .iter()
+            .map(|&(w_type, idx)| {
+                let widget = self.widget(w_type, idx);
+                (w_type, idx, widget.bounds(), widget.visual_bounds())
+            })
+            .collect()
+    }
+
+    pub(crate) fn hover_dropdown(&mut self, idx: usize, x: i32, y: i32) {
         self.dropdownbuttons[idx].hover(x, y);
     }
 
+    fn widget(&self, w_type: WidgetType, idx: usize) -> &dyn Widget {
+        match w_type {
+            WidgetType::Button => &self.buttons[idx],
+            WidgetType::TextField => &self.textfields[idx],
+            WidgetType::Fader => &self.faders[idx],
+            WidgetType::DropdownButton => &self.dropdownbuttons[idx],
+            WidgetType::Custom => &*self.custom[idx],
+        }
+    }
+
+    fn widget_mut(&mut self, w_type: WidgetType, idx: usize) -> Option<&mut dyn Widget> {
+        match w_type {
+            WidgetType::Button => self.buttons.get_mut(idx).map(|w| w as &mut dyn Widget),
+            WidgetType::TextField => self.textfields.get_mut(idx).map(|w| w as &mut dyn Widget),
+            WidgetType::Fader => self.faders.get_mut(idx).map(|w| w as &mut dyn Widget),
+            WidgetType::DropdownButton => self.dropdownbuttons.get_mut(idx).map(|w| w as &mut dyn Widget),
+            WidgetType::Custom => self.custom.get_mut(idx).map(|w| &mut **w as &mut dyn Widget),
+        }
+    }
+
+    /// Resizes relatively sized widgets and moves the panel to its anchor in a window of `window` size
+    pub(crate) fn relayout(&mut self, window: (u32, u32)) {
+        self.resize_relative(window);
+        let extent = (
+            (self.bounds.right() - self.origin.0).max(0) as u32,
+            (self.bounds.bottom() - self.origin.1).max(0) as u32,
+        );
+        let origin = self.anchor.place(window, extent, self.position);
+        self.move_by(origin.0 - self.origin.0, origin.1 - self.origin.1);
+    }
+
+    // Child panels aren't anchored, but their widgets may be sized relative to the window
+    fn resize_relative(&mut self, window: (u32, u32)) {
+        for (w_type, idx, width, height) in self.relative_sizes.clone() {
+            if let Some(widget) = self.widget_mut(w_type, idx) {
+                widget.resize(
+                    width.map(|percent| (window.0 as f32 * percent / 100.0) as u32),
+                    height.map(|percent| (window.1 as f32 * percent / 100.0) as u32),
+                );
+            }
+        }
+        self.children.iter_mut().for_each(|child| child.resize_relative(window));
+        self.update_layout();
+    }
+
+    // Where a widget at (0, 0) relative to the panel currently is
+    fn widget_offset(&self) -> (i32, i32) {
+        (
+            self.origin.0 + self.content_offset.0 - self.scroll.0,
+            self.origin.1 + self.content_offset.1 - self.scroll.1,
+        )
+    }
+
+    fn move_by(&mut self, x: i32, y: i32) {
+        self.shift_widgets(x, y);
+        self.origin = (self.origin.0 + x, self.origin.1 + y);
+        self.update_layout();
+    }
+
+    fn shift_widgets(&mut self, x: i32, y: i32) {
+        self.buttons.iter_mut().for_each(|btn| btn.shift(x, y));
+        self.textfields.iter_mut().for_each(|tf| tf.shift(x, y));
+        self.faders.iter_mut().for_each(|fd| fd.shift(x, y));
+        self.dropdownbuttons.iter_mut().for_each(|ddb| ddb.shift(x, y));
+        self.custom.iter_mut().for_each(|custom| custom.shift(x, y));
+        self.children.iter_mut().for_each(|child| child.move_by(x, y));
+    }
+
+    fn update_content_offset(&mut self) {
+        let title_height = match &self.title {
+            Some(_) => TITLE_BAR_HEIGHT as i32,
+            None => 0,
+        };
+        let offset = (self.padding, self.padding + title_height);
+        self.shift_widgets(offset.0 - self.content_offset.0, offset.1 - self.content_offset.1);
+        self.content_offset = offset;
+        self.update_layout();
+    }
+
+    // Panels without any chrome are invisible and as large as the bounding box of their widgets
+    fn has_frame(&self) -> bool {
+        self.size.is_some()
+            || self.background.is_some()
+            || self.border.is_some()
+            || self.title.is_some()
+            || self.padding > 0
+    }
+
+    pub(crate) fn title_bar(&self) -> Option<Rect> {
+        self.title.as_ref().map(|_| rect!(self.bounds.x, self.bounds.y, self.bounds.w, TITLE_BAR_HEIGHT))
+    }
+
+    fn close_button(&self) -> Option<Rect> {
+        let title_bar = self.title_bar().filter(|_| self.closable)?;
+        Some(rect!(title_bar.right() - title_bar.h, title_bar.y, title_bar.h, title_bar.h))
+    }
+
+    pub(crate) fn close_button_at(&self, x: i32, y: i32) -> bool {
+        self.close_button().is_some_and(|button| in_bounds(&button, x, y))
+    }
+
+    /// True if (x, y) is on the part of the title bar that drags the panel
+    pub(crate) fn drag_handle_at(&self, x: i32, y: i32) -> bool {
+        self.draggable
+            && self.title_bar().is_some_and(|title_bar| in_bounds(&title_bar, x, y))
+            && !self.close_button_at(x, y)
+    }
+
+    /// Area of a scrollable panel that its widgets are visible in
+    fn viewport(&self) -> Rect {
+        let title_height = self.title_bar().map_or(0, |title_bar| title_bar.h);
+        rect!(self.bounds.x, self.bounds.y + title_height, self.bounds.w, (self.bounds.h - title_height).max(0))
+    }
+
+    fn max_scroll(&self) -> (i32, i32) {
+        if !self.scrollable {
+            return (0, 0);
+        }
+        let viewport = self.viewport();
+        let content = bounding_box(self
+            .widget_rects()
+            .into_iter()
+            .map(|widget| widget.3)
+            .chain(self.children.iter().map(|child| child.bounds))
+            .collect()
+        );
+        (
+            (content.right() + self.scroll.0 + self.padding - viewport.right()).max(0),
+            (content.bottom() + self.scroll.1 + self.padding - viewport.bottom()).max(0),
+        )
+    }
+
+    /// Track and thumb of the scrollbar along `orientation`, if the content overflows that way
+    fn scrollbar(&self, orientation: Orientation) -> Option<(Rect, Rect)> {
+        let viewport = self.viewport();
+        let max = self.max_scroll();
+        match orientation {
+            Orientation::Vertical if max.1 > 0 => {
+                let track = rect!(viewport.right() - SCROLLBAR_WIDTH, viewport.y, SCROLLBAR_WIDTH, viewport.h);
+                let length = (track.h * viewport.h / (viewport.h + max.1)).max(SCROLLBAR_WIDTH);
+                let start = track.y + (track.h - length) * self.scroll.1 / max.1;
+                Some((track, rect!(track.x, start, SCROLLBAR_WIDTH, length)))
+            },
+            Orientation::Horizontal if max.0 > 0 => {
+                let track = rect!(viewport.x, viewport.bottom() - SCROLLBAR_WIDTH, viewport.w, SCROLLBAR_WIDTH);
+                let length = (track.w * viewport.w / (viewport.w + max.0)).max(SCROLLBAR_WIDTH);
+                let start = track.x + (track.w - length) * self.scroll.0 / max.0;
+                Some((track, rect!(start, track.y, length, SCROLLBAR_WIDTH)))
+            },
+            _ => None,
+        }
+    }
+
+    fn scrollbar_under(&self, x: i32, y: i32) -> Option<Orientation> {
+        [Orientation::Vertical, Orientation::Horizontal]
+            .into_iter()
+            .find(|&orientation| self
+                .scrollbar(orientation)
+                .is_some_and(|(track, _)| in_bounds(&track, x, y))
+            )
+    }
+
+    /// Distance from the start of the scrollbar's thumb to (x, y).
+    /// Pressing the track outside the thumb grabs the thumb at its center.
+    pub(crate) fn scrollbar_grab(&self, orientation: Orientation, x: i32, y: i32) -> i32 {
+        let Some((_, thumb)) = self.scrollbar(orientation) else {
+            return 0;
+        };
+        match (orientation, in_bounds(&thumb, x, y)) {
+            (Orientation::Vertical, true) => y - thumb.y,
+            (Orientation::Horizontal, true) => x - thumb.x,
+            (Orientation::Vertical, false) => thumb.h / 2,
+            (Orientation::Horizontal, false) => thumb.w / 2,
+        }
+    }
+
+    /// Scrolls so that the thumb of the scrollbar starts at `position`
+    pub(crate) fn drag_scrollbar(&mut self, orientation: Orientation, position: i32) {
+        let Some((track, thumb)) = self.scrollbar(orientation) else {
+            return;
+        };
+        let max = self.max_scroll();
+        match orientation {
+            Orientation::Vertical if track.h > thumb.h => {
+                self.scroll_to(self.scroll.0, (position - track.y) * max.1 / (track.h - thumb.h));
+            },
+            Orientation::Horizontal if track.w > thumb.w => {
+                self.scroll_to((position - track.x) * max.0 / (track.w - thumb.w), self.scroll.1);
+            },
+            _ => {},
+        }
+    }
+
+    // Scrolls the least amount needed for the widget to be fully visible
+    fn scroll_into_view(&mut self, w_type: WidgetType, idx: usize) {
+        self.scroll_rect_into_view(self.widget(w_type, idx).visual_bounds());
+    }
+
+    fn scroll_rect_into_view(&mut self, rect: Rect) {
+        if !self.scrollable {
+            return;
+        }
+        let viewport = self.viewport();
+        let distance = |start: i32, end: i32, view_start: i32, view_end: i32| {
+            if start < view_start {
+                start - view_start - self.padding
+            } else if end > view_end {
+                (end - view_end + self.padding).min(start - view_start)
+            } else {
+                0
+            }
+        };
+        self.scroll_by(
+            distance(rect.x, rect.right(), viewport.x, viewport.right()),
+            distance(rect.y, rect.bottom(), viewport.y, viewport.bottom()),
+        );
+    }
+
+    /// Moves the panel by the given amount. A dragged panel is no longer anchored to the window.
+    pub(crate) fn drag_by(&mut self, x: i32, y: i32) {
+        self.move_by(x, y);
+        self.anchor = Anchor::TopLeft;
+        self.position = self.origin;
+    }
+
+    fn draw_chrome(&self, canvas: &mut Canvas<Window>, fonts: &FontManager, font: &FontSource) -> Result<(), GuiError> {
+        if let Some(background) = self.background {
+            canvas.set_draw_color(background);
+            canvas.fill_rect(self.bounds)?;
+        }
+        if let (Some(title), Some(title_bar)) = (&self.title, self.title_bar()) {
+            canvas.set_draw_color(TITLE_BAR_COLOR);
+            canvas.fill_rect(title_bar)?;
+            let style = TextStyle { font, size: 16, color: TITLE_COLOR };
+            self.text_cache.draw(0, canvas, fonts, title, style, |width, height| rect!(
+                title_bar.x + 6,
+                title_bar.y + title_bar.h / 2 - height as i32 / 2,
+                width,
+                height
+            ))?;
+        }
+        if let Some(button) = self.close_button() {
+            let cross = rect!(
+                button.x + CLOSE_BUTTON_MARGIN,
+                button.y + CLOSE_BUTTON_MARGIN,
+                button.w - 2 * CLOSE_BUTTON_MARGIN,
+                button.h - 2 * CLOSE_BUTTON_MARGIN
+            );
+            canvas.set_draw_color(TITLE_COLOR);
+            canvas.draw_line(cross.top_left(), cross.bottom_right())?;
+            canvas.draw_line(cross.top_right(), cross.bottom_left())?;
+        }
+        if let Some(border) = self.border {
+            canvas.set_draw_color(border);
+            canvas.draw_rect(self.bounds)?;
+        }
+        Ok(())
+    }
+
+    fn renderable(&self, w_type: WidgetType, idx: usize) -> &dyn Render {
+        match w_type {
+            WidgetType::Button => &self.buttons[idx],
+            WidgetType::TextField => &self.textfields[idx],
+            WidgetType::Fader => &self.faders[idx],
+            WidgetType::DropdownButton => &self.dropdownbuttons[idx],
+            WidgetType::Custom => &*self.custom[idx],
+        }
+    }
+
+    /// Recomputes the bounds and focus order of the panel from its widgets, keeping the focused widget
+    fn update_layout(&mut self) {
+        let focused = self.active.map(|active| self.widget_order[active]);
+        let widgets: Vec<(WidgetType, usize, Rect)> = [
+            (WidgetType::Button, self.buttons.len()),
+            (WidgetType::TextField, self.textfields.len()),
+            (WidgetType::Fader, self.faders.len()),
+            (WidgetType::DropdownButton, self.dropdownbuttons.len()),
+            (WidgetType::Custom, self.custom.len()),
+        ]
+            .into_iter()
+            .flat_map(|(w_type, count)| (0..count).map(move |idx| (w_type, idx)))
+            .map(|(w_type, idx)| (w_type, idx, self.widget(w_type, idx).visual_bounds()))
+            .collect();
+        let content = bounding_box(widgets
+            .iter()
+            .map(|widget| widget.2)
+            .chain(self.children.iter().map(|child| child.bounds))
+            .collect()
+        );
+        self.bounds = match self.has_frame() {
+            true => {
+                let (w, h) = self.size.unwrap_or((
+                    (content.right() - self.origin.0 + self.padding).max(0) as u32,
+                    (content.bottom() - self.origin.1 + self.padding).max(0) as u32,
+                ));
+                rect!(self.origin.0, self.origin.1, w, h)
+            },
+            false => content,
+        };
+        self.widget_order = widget_order(widgets);
+        self.active = focused.and_then(|focused| self.widget_order.iter().position(|&widget| widget == focused));
+    }
+
+    fn not_found(&self, widget: WidgetType, id: WidgetId) -> GuiError {
+        match id {
+            WidgetId::Index(index) => GuiError::WidgetIndexOutOfRange { panel: self.name.to_string(), widget, index },
+            WidgetId::Name(id) => GuiError::UnknownWidget { panel: self.name.to_string(), widget, id: id.to_string() },
+        }
+    }
+
+    fn widget_count(&self, w_type: WidgetType) -> usize {
+        match w_type {
+            WidgetType::Button => self.buttons.len(),
+            WidgetType::TextField => self.textfields.len(),
+            WidgetType::Fader => self.faders.len(),
+            WidgetType::DropdownButton => self.dropdownbuttons.len(),
+            WidgetType::Custom => self.custom.len(),
+        }
+    }
+
     fn deselect_active(&mut self) {
         if let Some(active) = self.active {
             let active_widget = self.widget_order[active];
             match active_widget.0 {
                 WidgetType::TextField => self.textfields[active_widget.1].set_active(false),
                 WidgetType::DropdownButton => self.dropdownbuttons[active_widget.1].close(),
+                WidgetType::Custom => self.custom[active_widget.1].set_focused(false),
                 WidgetType::Fader => {},
                 WidgetType::Button => {}
             }
@@ -319,9 +1208,11 @@ where
     fn select_active(&mut self) {
         if let Some(active) = self.active {
             let active_widget = self.widget_order[active];
+            self.scroll_into_view(active_widget.0, active_widget.1);
             match active_widget.0 {
                 WidgetType::TextField => self.textfields[active_widget.1].set_active(true),
                 WidgetType::DropdownButton => self.dropdownbuttons[active_widget.1].open(),
+                WidgetType::Custom => self.custom[active_widget.1].set_focused(true),
                 WidgetType::Fader => {},
                 WidgetType::Button => {}
             }
@@ -334,44 +1225,96 @@ where
         }
         None
     }
+}
 
-    fn active_widget_index(&self) -> Option<usize> {
-        if let Some(active) = self.active {
-            return Some(self.widget_order[active].1);
-        }
-        None
+/// Type and index of `widget` after `removed` was taken out of the same panel, or `None` if it was `removed`
+pub(crate) fn reindex(widget: (WidgetType, usize), removed: (WidgetType, usize)) -> Option<(WidgetType, usize)> {
+    match widget {
+        _ if widget == removed => None,
+        (w_type, idx) if w_type == removed.0 && idx > removed.1 => Some((w_type, idx - 1)),
+        _ => Some(widget),
     }
 }
 
-fn widget_order<T: Copy>(btns: Vec<Button<T>>, tfs: Vec<TextField>, fdrs: Vec<Fader>, ddbtns: Vec<DropdownButton>)
--> Vec<(WidgetType, usize)> {
-    let mut widgets: Vec<(WidgetType, usize, i32, i32)> = btns
-        .iter()
-        .enumerate()
-        .map(|(idx, btn)| (WidgetType::Button, idx, btn.visual_bounds().x, btn.visual_bounds().y))
-        .collect();
-
-    let mut tfs: Vec<(WidgetType, usize, i32, i32)> = tfs
-        .iter()
-        .enumerate()
-        .map(|(idx, tf)| (WidgetType::TextField, idx, tf.visual_bounds().x, tf.visual_bounds().y))
-        .collect();
-
-    let mut fdrs: Vec<(WidgetType, usize, i32, i32)> = fdrs
-        .iter()
-        .enumerate()
-        .map(|(idx, fdr)| (WidgetType::Fader, idx, fdr.visual_bounds().x, fdr.visual_bounds().y))
-        .collect();
-    
-    let mut ddbtns: Vec<(WidgetType, usize, i32, i32)> = ddbtns
-        .iter()
-        .enumerate()
-        .map(|(idx, ddb)| (WidgetType::DropdownButton, idx, ddb.visual_bounds().x, ddb.visual_bounds().y))
-        .collect();
-    widgets.append(&mut tfs);
-    widgets.append(&mut fdrs);
-    widgets.append(&mut ddbtns);
-    widgets.sort_unstable_by_key(|widget| (widget.3, widget.2));
+// Focus order runs top to bottom, then left to right
+fn widget_order(mut widgets: Vec<(WidgetType, usize, Rect)>) -> Vec<(WidgetType, usize)> {
+    widgets.sort_by_key(|widget| (widget.2.y, widget.2.x));
     widgets.iter().map(|widget| (widget.0, widget.1)).collect()
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reindex_moves_later_widgets_of_the_removed_type_down() {
+        let removed = (WidgetType::Fader, 1);
+        assert_eq!(reindex((WidgetType::Fader, 0), removed), Some((WidgetType::Fader, 0)));
+        assert_eq!(reindex((WidgetType::Fader, 1), removed), None);
+        assert_eq!(reindex((WidgetType::Fader, 2), removed), Some((WidgetType::Fader, 1)));
+        assert_eq!(reindex((WidgetType::Button, 2), removed), Some((WidgetType::Button, 2)));
+    }
+
+    // Ten 40px tall buttons, 50px apart, in a 200px tall scrollable panel
+    fn list(padding: u32) -> Panel<()> {
+        let buttons = (0..10).map(|row| Button::new(0, row * 50, 100, 40)).collect();
+        Panel::new("list", (0, 0), buttons, vec![], vec![], vec![])
+            .size(100, 200)
+            .padding(padding)
+            .scrollable()
+    }
+
+    fn button_bounds(panel: &Panel<()>, idx: usize) -> Rect {
+        panel.buttons[idx].bounds()
+    }
+
+    #[test]
+    fn scrolling_is_clamped_to_the_content() {
+        let mut panel = list(0);
+        assert_eq!(panel.max_scroll(), (0, 290));
+        panel.scroll_to(0, 1000);
+        assert_eq!(panel.scroll, (0, 290));
+        assert_eq!(button_bounds(&panel, 9).bottom(), 200);
+        panel.scroll_by(-5, -1000);
+        assert_eq!(panel.scroll, (0, 0));
+        assert_eq!(button_bounds(&panel, 0).y, 0);
+    }
+
+    #[test]
+    fn content_that_fits_does_not_scroll() {
+        let mut panel = list(0).size(100, 1000);
+        assert_eq!(panel.max_scroll(), (0, 0));
+        assert!(panel.scrollbar(Orientation::Vertical).is_none());
+        panel.scroll_by(0, 50);
+        assert_eq!(panel.scroll, (0, 0));
+    }
+
+    #[test]
+    fn dragging_the_thumb_to_the_ends_of_the_track_scrolls_to_the_ends() {
+        let mut panel = list(0);
+        let (track, thumb) = panel.scrollbar(Orientation::Vertical).unwrap();
+        assert_eq!(track.h, 200);
+        panel.drag_scrollbar(Orientation::Vertical, track.bottom() - thumb.h);
+        assert_eq!(panel.scroll, (0, 290));
+        let (_, thumb) = panel.scrollbar(Orientation::Vertical).unwrap();
+        assert_eq!(thumb.bottom(), track.bottom());
+        panel.drag_scrollbar(Orientation::Vertical, track.y - 100);
+        assert_eq!(panel.scroll, (0, 0));
+    }
+
+    #[test]
+    fn focused_widgets_are_scrolled_just_into_view() {
+        let mut panel = list(10);
+        // Below the viewport: its bottom edge ends up `padding` above the viewport's
+        panel.scroll_into_view(WidgetType::Button, 5);
+        assert_eq!(panel.scroll, (0, 110));
+        assert_eq!(button_bounds(&panel, 5).bottom(), 190);
+        // Already visible: nothing moves
+        panel.scroll_into_view(WidgetType::Button, 4);
+        assert_eq!(panel.scroll, (0, 110));
+        // Above the viewport: its top edge ends up `padding` below the viewport's
+        panel.scroll_into_view(WidgetType::Button, 0);
+        assert_eq!(panel.scroll, (0, 0));
+        assert_eq!(button_bounds(&panel, 0).y, 10);
+    }
+}
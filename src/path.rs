@@ -0,0 +1,96 @@
+use std::borrow::Cow;
+use std::fmt;
+
+/// Names of the panels from a top-level panel down to the one holding a widget,
+/// e.g. `settings/audio` for a panel `audio` nested in `settings`.
+///
+/// Compares equal to its `/`-separated form, so a top-level panel matches its name:
+/// `path == "editor"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PanelPath(Vec<Cow<'static, str>>);
+
+impl PanelPath {
+    pub(crate) fn new(root: Cow<'static, str>) -> PanelPath {
+        PanelPath(vec![root])
+    }
+
+    pub(crate) fn child(&self, name: Cow<'static, str>) -> PanelPath {
+        let mut path = self.clone();
+        path.0.push(name);
+        path
+    }
+
+    /// The top-level panel
+    pub fn root(&self) -> &str {
+        &self.0[0]
+    }
+
+    /// The panel holding the widget
+    pub fn name(&self) -> &str {
+        &self.0[self.0.len() - 1]
+    }
+
+    pub fn panels(&self) -> &[Cow<'static, str>] {
+        &self.0
+    }
+
+    /// Path to the panel holding this one, `None` for a top-level panel
+    pub(crate) fn parent(&self) -> Option<PanelPath> {
+        match self.0.len() {
+            1 => None,
+            len => Some(PanelPath(self.0[..len - 1].to_vec())),
+        }
+    }
+
+    pub(crate) fn contains(&self, panel: &str) -> bool {
+        self.0.iter().any(|name| name == panel)
+    }
+}
+
+impl fmt::Display for PanelPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.join("/"))
+    }
+}
+
+impl PartialEq<str> for PanelPath {
+    fn eq(&self, other: &str) -> bool {
+        let mut parts = other.split('/');
+        self.0.iter().all(|panel| parts.next() == Some(panel.as_ref())) && parts.next().is_none()
+    }
+}
+
+impl PartialEq<&str> for PanelPath {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn audio() -> PanelPath {
+        PanelPath::new(Cow::Borrowed("settings")).child(Cow::Borrowed("audio"))
+    }
+
+    #[test]
+    fn compares_equal_to_its_slash_separated_form() {
+        assert_eq!(audio(), "settings/audio");
+        assert_eq!(audio().to_string(), "settings/audio");
+    }
+
+    #[test]
+    fn differs_from_prefixes_and_extensions() {
+        assert_ne!(audio(), "settings");
+        assert_ne!(audio(), "audio");
+        assert_ne!(audio(), "settings/audio/output");
+        assert_ne!(audio(), "");
+    }
+
+    #[test]
+    fn parent_drops_the_innermost_panel() {
+        assert_eq!(audio().parent(), Some(PanelPath::new(Cow::Borrowed("settings"))));
+        assert_eq!(audio().parent().and_then(|parent| parent.parent()), None);
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{GuiEvent, GUI};
+
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::{MouseButton, MouseState, MouseWheelDirection};
+
+const LMB_STATE: u32 = 1;
+
+// Synthetic input, routed through GUI::inject
+impl<T, S> GUI<T, S>
+where
+    T: Copy + Default,
+{
+    pub fn simulate_mouse_move(&mut self, x: i32, y: i32) -> GuiEvent<T> {
+        let (x, y) = self.to_window(x, y);
+        self.inject(mouse_motion(x, y, 0))
+    }
+
+    /// Moves to (x, y), then presses and releases the left mouse button.
+    pub fn simulate_click(&mut self, x: i32, y: i32) -> GuiEvent<T> {
+        self.simulate_mouse_move(x, y);
+        let (x, y) = self.to_window(x, y);
+        self.inject(mouse_button(true, x, y));
+        self.inject(mouse_button(false, x, y))
+    }
+
+    /// Presses at `from`, drags to `to` and releases there.
+    /// Returns the last event that wasn't `GuiEvent::None`.
+    pub fn simulate_drag(&mut self, from: (i32, i32), to: (i32, i32)) -> GuiEvent<T> {
+        self.simulate_mouse_move(from.0, from.1);
+        let from = self.to_window(from.0, from.1);
+        let to = self.to_window(to.0, to.1);
+        self.inject(mouse_button(true, from.0, from.1));
+        let drag = self.inject(mouse_motion(to.0, to.1, LMB_STATE));
+        match self.inject(mouse_button(false, to.0, to.1)) {
+            GuiEvent::None => drag,
+            release => release,
+        }
+    }
+
+    /// Turns the mouse wheel with the cursor at (x, y). Positive `dy` scrolls up, like SDL.
+    pub fn simulate_wheel(&mut self, x: i32, y: i32, dx: i32, dy: i32) -> GuiEvent<T> {
+        let (x, y) = self.to_window(x, y);
+        self.inject(Event::MouseWheel {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            x: dx,
+            y: dy,
+            direction: MouseWheelDirection::Normal,
+            precise_x: dx as f32,
+            precise_y: dy as f32,
+            mouse_x: x,
+            mouse_y: y,
+        })
+    }
+
+    pub fn simulate_key(&mut self, keycode: Keycode) -> GuiEvent<T> {
+        self.simulate_key_mod(keycode, Mod::NOMOD)
+    }
+
+    pub fn simulate_key_mod(&mut self, keycode: Keycode, keymod: Mod) -> GuiEvent<T> {
+        self.inject(Event::KeyDown {
+            timestamp: 0,
+            window_id: 0,
+            keycode: Some(keycode),
+            scancode: None,
+            keymod,
+            repeat: false,
+        })
+    }
+
+    pub fn simulate_text(&mut self, text: &str) -> GuiEvent<T> {
+        self.inject(Event::TextInput {
+            timestamp: 0,
+            window_id: 0,
+            text: text.to_string(),
+        })
+    }
+
+    // Simulated coordinates are unscaled, like widget positions, while real mouse events are in window pixels.
+    // Maps to the center of the scaled pixel so that the handler maps it back to (x, y).
+    fn to_window(&self, x: i32, y: i32) -> (i32, i32) {
+        let scale = self.scale();
+        (((x as f32 + 0.5) * scale) as i32, ((y as f32 + 0.5) * scale) as i32)
+    }
+}
+
+fn mouse_motion(x: i32, y: i32, state: u32) -> Event {
+    Event::MouseMotion {
+        timestamp: 0,
+        window_id: 0,
+        which: 0,
+        mousestate: MouseState::from_sdl_state(state),
+        x,
+        y,
+        xrel: 0,
+        yrel: 0,
+    }
+}
+
+fn mouse_button(down: bool, x: i32, y: i32) -> Event {
+    match down {
+        true => Event::MouseButtonDown {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            mouse_btn: MouseButton::Left,
+            clicks: 1,
+            x,
+            y,
+        },
+        false => Event::MouseButtonUp {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            mouse_btn: MouseButton::Left,
+            clicks: 1,
+            x,
+            y,
+        },
+    }
+}
@@ -1,28 +1,35 @@
 
-use crate::{Render, rect};
-use super::Widget;
+use crate::{FontManager, FontSource, GuiError, Render, rect};
+use super::{TextCache, TextStyle, Widget};
 
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
-use sdl2::render::{Canvas, TextureQuery};
-use sdl2::ttf::Sdl2TtfContext;
+use sdl2::render::Canvas;
 use sdl2::video::Window;
 
+use std::borrow::Cow;
+
 const DEFAULT_BTN_COL: Color = Color::RGB(85, 85, 85);
+const TOOLTIP_COLOR: Color = Color::RGB(255, 255, 210);
+const TOOLTIP_PADDING: i32 = 4;
 
 //Button
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Button<T>
 where
     T: Copy,
 {
+    id: Option<Cow<'static, str>>,
     color: Color,
-    label: &'static str,
+    label: Cow<'static, str>,
     font_size: u16,
     rect: Rect,
     callback: T,
     is_pressed: bool,
     is_hovered: bool,
+    tooltip: Cow<'static, str>,
+    font: Option<FontSource>,
+    text_cache: TextCache,
 }
 
 impl<T> Widget for Button<T> where T: Copy {
@@ -32,6 +39,12 @@ impl<T> Widget for Button<T> where T: Copy {
     fn bounds(&self) -> Rect {
         self.rect
     }
+    fn get_id(&self) -> Option<Cow<'static, str>> {
+        self.id.clone()
+    }
+    fn resize(&mut self, w: Option<u32>, h: Option<u32>) {
+        self.rect.resize(w.unwrap_or(self.rect.width()), h.unwrap_or(self.rect.height()));
+    }
 }
 
 impl<T> Button<T>
@@ -41,34 +54,62 @@ where
 {
     pub fn new(x: i32, y: i32, w: u32, h: u32) -> Button<T> {
         Button {
+            id: None,
             color: DEFAULT_BTN_COL,
-            label: "",
+            label: Cow::Borrowed(""),
             font_size: 24,
             rect: rect!(x, y, w, h),
             callback: T::default(),
             is_pressed: false,
             is_hovered: false,
+            tooltip: Cow::Borrowed(""),
+            font: None,
+            text_cache: TextCache::new(),
         }
     }
     pub const fn click(&self) -> T {
         self.callback
     }
+    /// Identifies the button in events and lookups instead of its index
+    pub fn id(mut self, id: impl Into<Cow<'static, str>>) -> Button<T> {
+        self.id = Some(id.into());
+        self
+    }
     pub const fn font_size(mut self, size: u16) -> Button<T> {
         self.font_size = size;
         self
     }
-    pub const fn label(mut self, s: &'static str) -> Button<T> {
-        self.label = s;
+    pub fn label(mut self, s: impl Into<Cow<'static, str>>) -> Button<T> {
+        self.label = s.into();
         self
     }
     pub const fn color_rgb(mut self, r: u8, g: u8, b: u8) -> Button<T> {
         self.color = Color::RGB(r, g, b);
         self
     }
+    /// Text shown below the button while it's hovered
+    pub fn tooltip(mut self, s: impl Into<Cow<'static, str>>) -> Button<T> {
+        self.tooltip = s.into();
+        self
+    }
+    /// Overrides the font of the panel for this widget
+    pub fn font(mut self, font: impl Into<FontSource>) -> Button<T> {
+        self.font = Some(font.into());
+        self
+    }
     pub const fn callback(mut self, cb: T) -> Button<T> {
         self.callback = cb;
         self
     }
+    pub fn get_label(&self) -> &str {
+        &self.label
+    }
+    pub fn set_label(&mut self, s: impl Into<Cow<'static, str>>) {
+        self.label = s.into();
+    }
+    pub fn set_tooltip(&mut self, s: impl Into<Cow<'static, str>>) {
+        self.tooltip = s.into();
+    }
     pub fn is_hovered(&mut self, b: bool) {
         self.is_hovered = b;
     }
@@ -78,7 +119,7 @@ impl<T> Render for Button<T>
 where
     T: Copy,
 {
-    fn render(&self, canvas: &mut Canvas<Window>) -> Result<(), String> {
+    fn render(&self, canvas: &mut Canvas<Window>) -> Result<(), GuiError> {
         canvas.set_draw_color(match self.is_hovered {
             false => self.color,
             true => {
@@ -97,39 +138,50 @@ where
 
     fn render_text(
         &self,
-        ttf: &Sdl2TtfContext,
+        fonts: &FontManager,
         canvas: &mut Canvas<Window>,
-        font: &'static str,
-    ) -> Result<(), String> {
-        let texture_creator = canvas.texture_creator();
-        let mut font = ttf.load_font(font, self.font_size)?;
-        font.set_style(sdl2::ttf::FontStyle::NORMAL);
-        let surface = font
-            .render(&self.label)
-            .blended(Color::RGB(0, 0, 0))
-            .map_err(|e| e.to_string())?;
-        let texture = texture_creator
-            .create_texture_from_surface(&surface)
-            .map_err(|e| e.to_string())?;
-        let TextureQuery { width, height, .. } = texture.query();
-
-        canvas.copy(
-            &texture,
-            None,
-            rect!(
-                self.rect.x + self.rect.w / 2 - width as i32 / 2,
-                self.rect.y + self.rect.h / 2 - height as i32 / 2,
-                width,
-                height
-            ),
-        )?;
+        font: &FontSource,
+    ) -> Result<(), GuiError> {
+        let font = self.font.as_ref().unwrap_or(font);
+        let style = TextStyle { font, size: self.font_size, color: Color::RGB(0, 0, 0) };
+        self.text_cache.draw(0, canvas, fonts, &self.label, style, |width, height| rect!(
+            self.rect.x + self.rect.w / 2 - width as i32 / 2,
+            self.rect.y + self.rect.h / 2 - height as i32 / 2,
+            width,
+            height
+        ))
+    }
 
-        if unsafe {crate::DEBUG}{
-            canvas.set_draw_color(Color::RGB(255, 0, 0));
-            canvas.draw_rect(self.bounds())?;
-            canvas.set_draw_color(Color::RGB(0, 255, 0));
-            canvas.draw_rect(self.visual_bounds())?;
+    fn render_overlay(
+        &self,
+        fonts: &FontManager,
+        canvas: &mut Canvas<Window>,
+        font: &FontSource,
+    ) -> Result<(), GuiError> {
+        if !self.is_hovered || self.tooltip.is_empty() {
+            return Ok(())
         }
-        Ok(())
+        let font = self.font.as_ref().unwrap_or(font);
+        let style = TextStyle { font, size: 12, color: Color::RGB(0, 0, 0) };
+        let (width, height) = fonts
+            .get(font, style.size)?
+            .size_of(&self.tooltip)
+            .map_err(|e| GuiError::Sdl(e.to_string()))?;
+        // Kept inside the window horizontally
+        let window_width = canvas.viewport().width() as i32;
+        let background = rect!(
+            self.rect.x.min(window_width - width as i32 - 2 * TOOLTIP_PADDING).max(0),
+            self.rect.y + self.rect.h + 2,
+            width as i32 + 2 * TOOLTIP_PADDING,
+            height as i32 + 2 * TOOLTIP_PADDING
+        );
+        canvas.set_draw_color(TOOLTIP_COLOR);
+        canvas.fill_rect(background)?;
+        self.text_cache.draw(1, canvas, fonts, &self.tooltip, style, |width, height| rect!(
+            background.x + TOOLTIP_PADDING,
+            background.y + TOOLTIP_PADDING,
+            width,
+            height
+        ))
     }
-}
\ No newline at end of file
+}
@@ -1,46 +1,101 @@
-use sdl2::{pixels::Color, rect::Rect, render::{Canvas, TextureQuery}, ttf::Sdl2TtfContext, video::Window};
+use sdl2::{pixels::Color, rect::Rect, render::Canvas, video::Window};
 
-use crate::{rect, Render};
+use crate::{rect, FontManager, FontSource, GuiError, Render};
 
-use super::Widget;
+use std::borrow::Cow;
+
+use super::{TextCache, TextStyle, Widget};
 
 //Button
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct DropdownButton
+pub struct DropdownButton<T>
+where
+    T: Copy,
 {
-    label: &'static str,
-    options: Vec<&'static str>,
+    id: Option<Cow<'static, str>>,
+    label: Cow<'static, str>,
+    options: Vec<(Cow<'static, str>, T)>,
     rect: Rect,
     is_open: bool,
     active: usize,
-    hovered: Option<usize>
+    hovered: Option<usize>,
+    font: Option<FontSource>,
+    text_cache: TextCache,
 }
 
-impl DropdownButton {
+impl<T> DropdownButton<T>
+where
+    T: Copy,
+{
     pub fn new(x: i32, y: i32) -> Self {
         DropdownButton {
-            label: "",
+            id: None,
+            label: Cow::Borrowed(""),
             options: vec![],
             rect: Rect::new(x, y, 100, 20),
             is_open: false,
             active: 0,
-            hovered: None
+            hovered: None,
+            font: None,
+            text_cache: TextCache::new(),
         }
     }
 
-    pub fn options(mut self, options: Vec<&'static str>) -> Self {
-        self.options = options;
+    /// Identifies the dropdown in events and lookups instead of its index
+    pub fn id(mut self, id: impl Into<Cow<'static, str>>) -> Self {
+        self.id = Some(id.into());
         self
     }
 
-    pub fn label(mut self, label: &'static str) -> Self {
-        self.label = label;
+    /// Options as (label, value) pairs. The value of the chosen option is reported in `GuiEvent::DropdownUpdate`.
+    pub fn options<S>(mut self, options: impl IntoIterator<Item = (S, T)>) -> Self
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        self.set_options(options);
+        self
+    }
+
+    /// Overrides the font of the panel for this widget
+    pub fn font(mut self, font: impl Into<FontSource>) -> Self {
+        self.font = Some(font.into());
+        self
+    }
+    pub fn label(mut self, label: impl Into<Cow<'static, str>>) -> Self {
+        self.label = label.into();
         self
     }
 
+    pub fn get_label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn set_label(&mut self, label: impl Into<Cow<'static, str>>) {
+        self.label = label.into();
+    }
+
+    pub fn get_options(&self) -> &[(Cow<'static, str>, T)] {
+        &self.options
+    }
+
+    /// Replaces the options. The selection falls back to the first option if it no longer exists.
+    pub fn set_options<S>(&mut self, options: impl IntoIterator<Item = (S, T)>)
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        self.options = options.into_iter().map(|(label, value)| (label.into(), value)).collect();
+        if self.active > self.options.len() {
+            self.active = 0;
+        }
+        if self.hovered.is_some_and(|hovered| hovered > self.options.len()) {
+            self.hovered = None;
+        }
+    }
+
     pub fn open(&mut self) {
         self.is_open = true;
-        self.hovered = Some(1)
+        // An empty dropdown opens without a hovered option, so clicking it again just closes it
+        self.hovered = (!self.options.is_empty()).then_some(1);
     }
 
     pub fn close(&mut self) {
@@ -53,6 +108,10 @@ impl DropdownButton {
     }
 
     pub fn next(&mut self) -> bool {
+        if self.options.is_empty() {
+            self.hovered = None;
+            return false;
+        }
         if self.hovered == None {
             self.hovered = Some(1)
         }
@@ -80,12 +139,16 @@ impl DropdownButton {
         self.hovered = None;
     }
 
-    pub fn click(&mut self) -> Option<&'static str> {
-        println!("self.hovered: {:?}", self.hovered);
+    /// Label and value of the selected option
+    pub fn selected(&self) -> Option<&(Cow<'static, str>, T)> {
+        self.options.get(self.active.saturating_sub(1))
+    }
 
-        if self.is_open && self.hovered.is_some(){
+    /// Returns the label and value of the option chosen by this click, if any
+    pub fn click(&mut self) -> Option<(Cow<'static, str>, T)> {
+        if let Some(option) = self.hovered.filter(|_| self.is_open).and_then(|hovered| self.options.get(hovered.checked_sub(1)?)) {
+            let result = Some(option.clone());
             self.active = self.hovered.unwrap();
-            let result = Some(self.options[self.active.saturating_sub(1)]);
             self.close();
             return result
         } else if !self.is_open {
@@ -97,7 +160,7 @@ impl DropdownButton {
     }
 }
 
-impl Widget for DropdownButton {
+impl<T> Widget for DropdownButton<T> where T: Copy {
     fn bounds(&self) -> sdl2::rect::Rect {
         self.rect
     }
@@ -105,29 +168,76 @@ impl Widget for DropdownButton {
         self.rect.x += x;
         self.rect.y += y;
     }
+    fn get_id(&self) -> Option<Cow<'static, str>> {
+        self.id.clone()
+    }
 
-    fn visual_bounds(&self) -> sdl2::rect::Rect {
-        if self.is_open {
-            rect!(
-                self.rect.x, self.rect.y, self.rect.w, self.rect.h * (self.options.len()+1) as i32
-            )
-        } else {
-            self.rect
+    // The height of a dropdown is the height of its option rows, so only the width follows the window
+    fn resize(&mut self, w: Option<u32>, _h: Option<u32>) {
+        if let Some(w) = w {
+            self.rect.set_width(w);
+        }
+    }
+
+    fn overlay_bounds(&self) -> Option<sdl2::rect::Rect> {
+        match self.is_open && !self.options.is_empty() {
+            true => Some(rect!(
+                self.rect.x, self.rect.y + self.rect.h, self.rect.w, self.rect.h * self.options.len() as i32
+            )),
+            false => None,
         }
     }
 }
 
 
-impl Render for DropdownButton {
-    fn render(&self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>) -> Result<(), String> {
+impl<T> Render for DropdownButton<T>
+where
+    T: Copy,
+{
+    fn render(&self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>) -> Result<(), GuiError> {
         canvas.set_draw_color(Color::RGB(200, 200, 200));
         canvas.fill_rect(self.rect)?;
         canvas.set_draw_color(Color::RGB(0, 0, 0));
         canvas.draw_rect(self.rect)?;
+        Ok(())
+    }
+
+    fn render_text(
+        &self,
+        fonts: &FontManager,
+        canvas: &mut Canvas<Window>,
+        font: &FontSource,
+    ) -> Result<(), GuiError> {
+        let font = self.font.as_ref().unwrap_or(font);
+        let style = TextStyle { font, size: 16, color: Color::RGB(0, 0, 0) };
+        let active = self.options.get(self.active.saturating_sub(1)).map_or("", |(label, _)| label.as_ref());
+        self.text_cache.draw(0, canvas, fonts, active, style, |width, height| rect!(
+            self.rect.x + 10,
+            self.rect.y + self.rect.h / 2 - height as i32 / 2,
+            width,
+            height
+        ))?;
+
+        let style = TextStyle { font, size: 12, color: Color::RGB(200, 200, 200) };
+        self.text_cache.draw(1, canvas, fonts, &self.label, style, |width, height| rect!(
+            self.rect.x, 
+            self.rect.y.saturating_sub(height as i32),
+            width, 
+            height
+        ))
+    }
+
+    // The option list
+    fn render_overlay(
+        &self,
+        fonts: &FontManager,
+        canvas: &mut Canvas<Window>,
+        font: &FontSource,
+    ) -> Result<(), GuiError> {
         if !self.is_open {
             return Ok(())
         }
-        for i in 0..=self.options.len() {
+        for i in 1..=self.options.len() {
             let rect = rect!(
                 self.rect.x, self.rect.y + i as i32 * self.rect.h, self.rect.w, self.rect.h
             );
@@ -142,97 +252,22 @@ impl Render for DropdownButton {
                 (self.rect.x, self.rect.y + (i as i32 * self.rect.h)), 
                 (self.rect.x+self.rect.w, self.rect.y + (i as i32 * self.rect.h))
             )?;
-        }   
-        Ok(())
-    }
-
-    fn render_text(
-        &self,
-        ttf: &Sdl2TtfContext,
-        canvas: &mut Canvas<Window>,
-        font_path: &'static str,
-    ) -> Result<(), String> {
-        let texture_creator = canvas.texture_creator();
-        let mut font = ttf.load_font(font_path, 16)?;
-        font.set_style(sdl2::ttf::FontStyle::NORMAL);
-
-        let surface = font
-            .render(&self.options[self.active.saturating_sub(1)])
-            .blended(Color::RGB(0, 0, 0))
-            .map_err(|e| e.to_string())?;
-        let texture = texture_creator
-            .create_texture_from_surface(&surface)
-            .map_err(|e| e.to_string())?;
-        let TextureQuery { width, height, .. } = texture.query();
-
-        canvas.copy(
-            &texture,
-            None,
-            rect!(
-                self.rect.x + 10,
-                self.rect.y + self.rect.h / 2 - height as i32 / 2,
-                width,
-                height
-            ),
-        )?;
-
-        if self.is_open {
-            for (idx, option) in self.options.iter().enumerate() {
-                let surface = font
-                    .render(option)
-                    .blended(Color::RGB(0, 0, 0))
-                    .map_err(|e| e.to_string())?;
-                let texture = texture_creator
-                    .create_texture_from_surface(&surface)
-                    .map_err(|e| e.to_string())?;
-                let TextureQuery { width, height, .. } = texture.query();
-                let rect = rect!(
-                    self.rect.x + 5, 
-                    self.rect.y + ((1 + idx) as i32 * self.rect.h) + 3, 
-                    width, 
-                    height
-                );
-                canvas.copy(
-                    &texture,
-                    None,
-                    rect
-                )?;
-            }
         }
-
-
-        if !self.label.is_empty(){
-            let mut font = ttf.load_font(font_path, 12)?;
-            font.set_style(sdl2::ttf::FontStyle::NORMAL);
-
-            let surface = font
-                .render(&self.label)
-                .blended(Color::RGB(200, 200, 200))
-                .map_err(|e| e.to_string())?;
-            let label_tex = texture_creator
-                .create_texture_from_surface(&surface)
-                .map_err(|e| e.to_string())?;
-
-
-            let TextureQuery { width, height, .. } = label_tex.query();
-            canvas.copy(
-                &label_tex,
-                None,
-                rect!(
-                    self.rect.x, 
-                    self.rect.y.saturating_sub(height as i32),
-                    width, 
-                    height
-                ),
-            )?;
+        canvas.set_draw_color(Color::RGB(60, 60, 60));
+        if let Some(list) = self.overlay_bounds() {
+            canvas.draw_rect(list)?;
         }
 
-        if unsafe {crate::DEBUG}{
-            canvas.set_draw_color(Color::RGB(255, 0, 0));
-            canvas.draw_rect(self.bounds())?;
-            canvas.set_draw_color(Color::RGB(0, 255, 0));
-            canvas.draw_rect(self.visual_bounds())?;
+        let font = self.font.as_ref().unwrap_or(font);
+        let style = TextStyle { font, size: 16, color: Color::RGB(0, 0, 0) };
+        for (idx, (label, _)) in self.options.iter().enumerate() {
+            self.text_cache.draw(2 + idx, canvas, fonts, label, style, |width, height| rect!(
+                self.rect.x + 5, 
+                self.rect.y + ((1 + idx) as i32 * self.rect.h) + 3, 
+                width, 
+                height
+            ))?;
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -1,11 +1,17 @@
-use crate::{Render, rect};
-use super::{Orientation, Widget};
+use crate::{FontManager, FontSource, GuiError, Render, rect};
+use super::{Orientation, TextCache, TextStyle, Widget};
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
-use sdl2::render::TextureQuery;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Fader {
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fader<T>
+where
+    T: Copy,
+{
+    id: Option<Cow<'static, str>>,
+    callback: T,
     position: (i32, i32),
     length: i32,
     orientation: Orientation,
@@ -13,12 +19,26 @@ pub struct Fader {
     range: (f32, f32),
     is_hovered: bool,
     display_on_hover: bool,
+    font: Option<FontSource>,
+    text_cache: TextCache,
 }
 
-impl Widget for Fader {
+impl<T> Widget for Fader<T> where T: Copy {
     fn shift(&mut self, x: i32, y: i32) {
         self.position = (self.position.0 + x, self.position.1 + y);
     }
+    fn get_id(&self) -> Option<Cow<'static, str>> {
+        self.id.clone()
+    }
+    fn resize(&mut self, w: Option<u32>, h: Option<u32>) {
+        let length = match self.orientation {
+            Orientation::Horizontal => w,
+            Orientation::Vertical => h,
+        };
+        if let Some(length) = length {
+            self.length = length as i32;
+        }
+    }
     fn visual_bounds(&self) -> Rect {
         match self.orientation {
             Orientation::Horizontal => rect!(
@@ -55,9 +75,15 @@ impl Widget for Fader {
     }
 }
 
-impl Fader {
-    pub fn new(x: i32, y: i32, w: i32) -> Fader {
+impl<T> Fader<T>
+where
+    T: Copy,
+    T: Default
+{
+    pub fn new(x: i32, y: i32, w: i32) -> Fader<T> {
         Fader {
+            id: None,
+            callback: T::default(),
             position: (x, y),
             length: w,
             value: 0.0,
@@ -65,15 +91,43 @@ impl Fader {
             orientation: Orientation::Horizontal,
             is_hovered: false,
             display_on_hover: false,
+            font: None,
+            text_cache: TextCache::new(),
         }
     }
+}
+
+impl<T> Fader<T>
+where
+    T: Copy,
+{
+    /// Identifies the fader in events and lookups instead of its index
+    pub fn id(mut self, id: impl Into<Cow<'static, str>>) -> Fader<T> {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Value reported with the events of this fader
+    pub const fn callback(mut self, cb: T) -> Fader<T> {
+        self.callback = cb;
+        self
+    }
+
+    pub const fn get_callback(&self) -> T {
+        self.callback
+    }
 
-    pub const fn display_on_hover(mut self) -> Fader {
+    pub const fn display_on_hover(mut self) -> Fader<T> {
         self.display_on_hover = true;
         self
     }
 
-    pub const fn vertical(mut self) -> Fader {
+    /// Overrides the font of the panel for this widget
+    pub fn font(mut self, font: impl Into<FontSource>) -> Fader<T> {
+        self.font = Some(font.into());
+        self
+    }
+    pub const fn vertical(mut self) -> Fader<T> {
         self.orientation = Orientation::Vertical;
         self
     }
@@ -82,7 +136,7 @@ impl Fader {
         self.orientation
     }
 
-    pub fn initial(mut self, value: f32) -> Fader {
+    pub fn initial(mut self, value: f32) -> Fader<T> {
         self.value = (value - self.range.0) / (self.range.1 - self.range.0);
         self
     }
@@ -91,7 +145,7 @@ impl Fader {
         self.range.0 + self.value * (self.range.1 - self.range.0)
     }
 
-    pub const fn range(mut self, min: f32, max: f32) -> Fader {
+    pub const fn range(mut self, min: f32, max: f32) -> Fader<T> {
         self.range = (min, max);
         self
     }
@@ -112,15 +166,12 @@ impl Fader {
         }
     }
 
-    pub fn set_fader_value(&mut self, value: f32) {
+    pub fn set_fader_value(&mut self, value: f32) -> Result<(), GuiError> {
         if value > self.range.1 || value < self.range.0 {
-            panic!("Incorrect fader value: {} - (Range is [{}, {}])", value, self.range.0, self.range.1)
-        }
-        if value == 0.0 { 
-            self.value = 0.0;
-            return
+            return Err(GuiError::ValueOutOfRange { value, min: self.range.0, max: self.range.1 });
         }
-        self.value = value/self.range.1
+        self.value = (value - self.range.0) / (self.range.1 - self.range.0);
+        Ok(())
     }
 
     pub fn drag(&mut self, x: i32, y: i32) {
@@ -151,8 +202,11 @@ impl Fader {
     }
 }
 
-impl Render for Fader {
-    fn render(&self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>) -> Result<(), String> {
+impl<T> Render for Fader<T>
+where
+    T: Copy,
+{
+    fn render(&self, canvas: &mut sdl2::render::Canvas<sdl2::video::Window>) -> Result<(), GuiError> {
         let lerp = (self.value * self.length as f32) as i32;
         canvas.set_draw_color(sdl2::pixels::Color::RGB(25, 25, 25));
 
@@ -223,58 +277,39 @@ impl Render for Fader {
 
     fn render_text(
             &self,
-            ttf: &sdl2::ttf::Sdl2TtfContext,
+            fonts: &FontManager,
             canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
-            font_path: &'static str,
-        ) -> Result<(), String> {
-        if unsafe {crate::DEBUG} {
-            canvas.set_draw_color(Color::RGB(255, 0, 0));
-            canvas.draw_rect(self.bounds())?;
-            canvas.set_draw_color(Color::RGB(0, 255, 0));
-            canvas.draw_rect(self.visual_bounds())?;
-        }
-        let texture_creator = canvas.texture_creator();
+            font: &FontSource,
+        ) -> Result<(), GuiError> {
+        let font = self.font.as_ref().unwrap_or(font);
         if (self.is_hovered && self.display_on_hover) || !self.display_on_hover {
-            let mut font = ttf.load_font(font_path, 12)?;
-            font.set_style(sdl2::ttf::FontStyle::NORMAL);
-
-            let surface = font
-                .render(&format!("{:.2}", self.value()))
-                .solid(Color::RGB(200, 200, 200))
-                .map_err(|e| e.to_string())?;
-            let label_tex = texture_creator
-                .create_texture_from_surface(&surface)
-                .map_err(|e| e.to_string())?;
-
-
-            let TextureQuery { width, height, .. } = label_tex.query();
+            let style = TextStyle { font, size: 12, color: Color::RGB(200, 200, 200) };
             let rect = self.bounds();
-            let canvas_size = canvas.output_size()?;
-            let x = match self.orientation {
-                Orientation::Horizontal => match canvas_size.0 as i32 > rect.x + width as i32 {
-                    true => rect.x as i32,
-                    false => (canvas_size.0 - width) as i32,
-                },
-                Orientation::Vertical => rect.x + 25
-            };
+            // In unscaled coordinates, like the fader itself
+            let window_width = canvas.viewport().width() as i32;
+            self.text_cache.draw(0, canvas, fonts, &format!("{:.2}", self.value()), style, |width, height| {
+                let x = match self.orientation {
+                    Orientation::Horizontal => match window_width > rect.x + width as i32 {
+                        true => rect.x,
+                        false => window_width - width as i32,
+                    },
+                    Orientation::Vertical => rect.x + 25
+                };
 
-            let y = match self.orientation {
-                Orientation::Horizontal => rect.y.saturating_sub(height as i32),
-                Orientation::Vertical => rect.y
-            };
+                let y = match self.orientation {
+                    Orientation::Horizontal => rect.y.saturating_sub(height as i32),
+                    Orientation::Vertical => rect.y
+                };
 
-            canvas.copy(
-                &label_tex,
-                None,
                 rect!(
                     x,
                     y,
                     width, 
                     height
-                ),
-            )?;
+                )
+            })?;
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -2,20 +2,82 @@ mod button;
 mod fader;
 mod textfield;
 mod dropdownbutton;
+mod textcache;
 
 pub use fader::Fader;
 pub use textfield::TextField;
 pub use button::Button;
 pub use dropdownbutton::DropdownButton;
+pub use textcache::{TextCache, TextStyle};
 
-pub type WidgetData = (&'static str, WidgetType, usize);
+use crate::{Direction, PanelPath, Render};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+use std::borrow::Cow;
+
+pub type WidgetData = (PanelPath, WidgetType, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum WidgetType {
     Button,
     TextField,
     Fader,
-    DropdownButton
+    DropdownButton,
+    Custom
+}
+
+/// Identifies a widget among the widgets of its type in a panel: by the ID it was given
+/// with `.id(..)`, or by its index. Setters and getters accept either through `Into<WidgetId>`,
+/// and events report the ID if the widget has one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum WidgetId {
+    Index(usize),
+    Name(Cow<'static, str>),
+}
+
+impl From<usize> for WidgetId {
+    fn from(idx: usize) -> WidgetId {
+        WidgetId::Index(idx)
+    }
+}
+
+impl From<&'static str> for WidgetId {
+    fn from(name: &'static str) -> WidgetId {
+        WidgetId::Name(Cow::Borrowed(name))
+    }
+}
+
+impl From<String> for WidgetId {
+    fn from(name: String) -> WidgetId {
+        WidgetId::Name(Cow::Owned(name))
+    }
+}
+
+impl From<Cow<'static, str>> for WidgetId {
+    fn from(name: Cow<'static, str>) -> WidgetId {
+        WidgetId::Name(name)
+    }
+}
+
+/// Compares the ID a widget was given, so events can be matched with `id == "red"`
+impl PartialEq<str> for WidgetId {
+    fn eq(&self, other: &str) -> bool {
+        matches!(self, WidgetId::Name(name) if name == other)
+    }
+}
+
+impl PartialEq<&str> for WidgetId {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl std::fmt::Display for WidgetId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WidgetId::Index(idx) => write!(f, "{}", idx),
+            WidgetId::Name(name) => write!(f, "{}", name),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -32,10 +94,132 @@ pub enum TextAlign {
 }
 
 
+/// Any widget that can be added to a panel with `Panel::add_widget`
+#[derive(Debug, Clone)]
+pub enum AnyWidget<T>
+where
+    T: Copy,
+{
+    Button(Button<T>),
+    TextField(TextField<T>),
+    Fader(Fader<T>),
+    DropdownButton(DropdownButton<T>),
+    Custom(Box<dyn CustomWidget<T>>),
+}
+
+impl<T: Copy> AnyWidget<T> {
+    pub const fn widget_type(&self) -> WidgetType {
+        match self {
+            AnyWidget::Button(_) => WidgetType::Button,
+            AnyWidget::TextField(_) => WidgetType::TextField,
+            AnyWidget::Fader(_) => WidgetType::Fader,
+            AnyWidget::DropdownButton(_) => WidgetType::DropdownButton,
+            AnyWidget::Custom(_) => WidgetType::Custom,
+        }
+    }
+
+    pub(crate) fn shift(&mut self, x: i32, y: i32) {
+        match self {
+            AnyWidget::Button(button) => button.shift(x, y),
+            AnyWidget::TextField(textfield) => textfield.shift(x, y),
+            AnyWidget::Fader(fader) => fader.shift(x, y),
+            AnyWidget::DropdownButton(dropdown) => dropdown.shift(x, y),
+            AnyWidget::Custom(custom) => custom.shift(x, y),
+        }
+    }
+}
+
+impl<T: Copy> From<Button<T>> for AnyWidget<T> {
+    fn from(button: Button<T>) -> AnyWidget<T> {
+        AnyWidget::Button(button)
+    }
+}
+
+impl<T: Copy> From<TextField<T>> for AnyWidget<T> {
+    fn from(textfield: TextField<T>) -> AnyWidget<T> {
+        AnyWidget::TextField(textfield)
+    }
+}
+
+impl<T: Copy> From<Fader<T>> for AnyWidget<T> {
+    fn from(fader: Fader<T>) -> AnyWidget<T> {
+        AnyWidget::Fader(fader)
+    }
+}
+
+impl<T: Copy> From<DropdownButton<T>> for AnyWidget<T> {
+    fn from(dropdown: DropdownButton<T>) -> AnyWidget<T> {
+        AnyWidget::DropdownButton(dropdown)
+    }
+}
+
+impl<T: Copy> From<Box<dyn CustomWidget<T>>> for AnyWidget<T> {
+    fn from(custom: Box<dyn CustomWidget<T>>) -> AnyWidget<T> {
+        AnyWidget::Custom(custom)
+    }
+}
+
 pub trait Widget {
     fn shift(&mut self, x: i32, y: i32);
     fn bounds(&self) -> sdl2::rect::Rect;
     fn visual_bounds(&self) -> sdl2::rect::Rect {
         self.bounds()
     }
+    /// Interactive area drawn by `Render::render_overlay`, e.g. an open dropdown list.
+    /// It takes hit-testing priority over every panel.
+    fn overlay_bounds(&self) -> Option<sdl2::rect::Rect> {
+        None
+    }
+    /// User-assigned ID reported in events instead of the widget's index. IDs must be unique
+    /// among the widgets of one type in a panel.
+    fn get_id(&self) -> Option<Cow<'static, str>> {
+        None
+    }
+    /// Called when the widget is sized relative to the window. `None` keeps the current size on that axis.
+    /// Fixed-size widgets ignore it.
+    fn resize(&mut self, _w: Option<u32>, _h: Option<u32>) {}
+}
+
+/// Input handling of user-defined widgets. Returned payloads are emitted as `GuiEvent::Custom`.
+pub trait Interactive<T> {
+    fn set_hovered(&mut self, _hovered: bool) {}
+    fn set_focused(&mut self, _focused: bool) {}
+    fn click(&mut self) -> Option<T> {
+        None
+    }
+    fn drag(&mut self, _x: i32, _y: i32) -> Option<T> {
+        None
+    }
+    fn arrow_key(&mut self, _dir: Direction) -> Option<T> {
+        None
+    }
+}
+
+/// A user-defined widget that can be added to a panel with `Panel::custom_widget`.
+/// Implemented for every `Clone` type implementing `Widget`, `Render` and `Interactive<T>`.
+pub trait CustomWidget<T>: Widget + Render + Interactive<T> {
+    fn clone_box(&self) -> Box<dyn CustomWidget<T>>;
+}
+
+impl<T, W> CustomWidget<T> for W
+where
+    W: Widget + Render + Interactive<T> + Clone + 'static,
+{
+    fn clone_box(&self) -> Box<dyn CustomWidget<T>> {
+        Box::new(self.clone())
+    }
+}
+
+impl<T> Clone for Box<dyn CustomWidget<T>> {
+    fn clone(&self) -> Box<dyn CustomWidget<T>> {
+        self.clone_box()
+    }
+}
+
+impl<T> std::fmt::Debug for dyn CustomWidget<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomWidget")
+            .field("bounds", &self.bounds())
+            .finish()
+    }
 }
\ No newline at end of file
@@ -0,0 +1,113 @@
+use crate::font::TextTexture;
+use crate::{FontManager, FontSource, GuiError};
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextStyle<'a> {
+    pub font: &'a FontSource,
+    pub size: u16,
+    pub color: Color,
+}
+
+struct CachedText {
+    text: String,
+    font: FontSource,
+    size: u16,
+    color: Color,
+    scale: f32,
+    texture: TextTexture,
+}
+
+impl CachedText {
+    fn matches(&self, text: &str, style: &TextStyle, scale: f32) -> bool {
+        self.text == text
+            && self.font == *style.font
+            && self.size == style.size
+            && self.color == style.color
+            && self.scale == scale
+    }
+}
+
+/// Text textures of a widget, indexed by slot (e.g. 0 for the caption, 1 for the label).
+/// A slot is re-rendered only when its string, font, size, color or the scale factor changes.
+///
+/// Cloning a cache yields an empty one.
+#[derive(Default)]
+pub struct TextCache {
+    slots: RefCell<HashMap<usize, CachedText>>,
+}
+
+impl TextCache {
+    pub fn new() -> TextCache {
+        TextCache::default()
+    }
+
+    /// Draws `text` at the rect returned by `place`, which receives the texture's width and height.
+    /// Empty strings draw nothing.
+    pub fn draw<F>(
+        &self,
+        slot: usize,
+        canvas: &mut Canvas<Window>,
+        fonts: &FontManager,
+        text: &str,
+        style: TextStyle,
+        place: F,
+    ) -> Result<(), GuiError>
+    where
+        F: FnOnce(u32, u32) -> Rect,
+    {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let mut slots = self.slots.borrow_mut();
+        let scale = fonts.scale();
+        let stale = !slots.get(&slot).is_some_and(|cached| cached.matches(text, &style, scale));
+        if stale {
+            slots.insert(slot, CachedText {
+                text: text.to_string(),
+                font: style.font.clone(),
+                size: style.size,
+                color: style.color,
+                scale,
+                texture: fonts.render_text(text, style.font, style.size, style.color)?,
+            });
+        }
+        let texture = &slots[&slot].texture;
+        let (width, height) = fonts.logical_size(texture);
+        Ok(canvas.copy(texture, None, place(width, height))?)
+    }
+
+    /// Drops all cached textures
+    pub fn clear(&self) {
+        self.slots.borrow_mut().clear();
+    }
+}
+
+impl Clone for TextCache {
+    fn clone(&self) -> TextCache {
+        TextCache::default()
+    }
+}
+
+impl PartialEq for TextCache {
+    fn eq(&self, _other: &TextCache) -> bool {
+        true
+    }
+}
+
+impl Eq for TextCache {}
+
+impl std::fmt::Debug for TextCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextCache")
+            .field("slots", &self.slots.borrow().len())
+            .finish()
+    }
+}
@@ -1,15 +1,21 @@
-use crate::{Render, rect};
-use super::{TextAlign, Widget};
+use crate::{FontManager, FontSource, GuiError, Render, rect};
+use super::{TextAlign, TextCache, TextStyle, Widget};
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
-use sdl2::render::{Canvas, TextureQuery};
-use sdl2::ttf::Sdl2TtfContext;
+use sdl2::render::Canvas;
 use sdl2::video::Window;
 
+use std::borrow::Cow;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct TextField {
+pub struct TextField<T>
+where
+    T: Copy,
+{
+    id: Option<Cow<'static, str>>,
+    callback: T,
     rect: Rect,
-    label: &'static str,
+    label: Cow<'static, str>,
     font_size: u16,
     content: String,
     is_active: bool,
@@ -17,15 +23,23 @@ pub struct TextField {
     transparent: bool,
     text_align: TextAlign,
     password: bool,
+    font: Option<FontSource>,
+    text_cache: TextCache,
 }
 
-impl Widget for TextField {
+impl<T> Widget for TextField<T> where T: Copy {
     fn shift(&mut self, x: i32, y: i32) {
         self.rect = rect!(self.rect.x + x, self.rect.y + y, self.rect.w, self.rect.h);
     }
     fn bounds(&self) -> Rect {
         self.rect
     }
+    fn get_id(&self) -> Option<Cow<'static, str>> {
+        self.id.clone()
+    }
+    fn resize(&mut self, w: Option<u32>, h: Option<u32>) {
+        self.rect.resize(w.unwrap_or(self.rect.width()), h.unwrap_or(self.rect.height()));
+    }
     fn visual_bounds(&self) -> Rect {
         if self.label.is_empty() || self.transparent {
             self.rect
@@ -40,11 +54,17 @@ impl Widget for TextField {
     }
 }
 
-impl TextField {
-    pub fn new(x: i32, y: i32, w: u32, h: u32) -> TextField {
+impl<T> TextField<T>
+where
+    T: Copy,
+    T: Default
+{
+    pub fn new(x: i32, y: i32, w: u32, h: u32) -> TextField<T> {
         TextField {
+            id: None,
+            callback: T::default(),
             rect: rect!(x, y, w, h),
-            label: "",
+            label: Cow::Borrowed(""),
             font_size: 24,
             content: String::new(),
             is_active: false,
@@ -52,32 +72,61 @@ impl TextField {
             transparent: false,
             text_align: TextAlign::Left(5),
             password: false,
+            font: None,
+            text_cache: TextCache::new(),
         }
     }
-    pub const fn get_label(&self) -> &str {
-        self.label
+}
+
+impl<T> TextField<T>
+where
+    T: Copy,
+{
+    /// Identifies the textfield in lookups instead of its index
+    pub fn id(mut self, id: impl Into<Cow<'static, str>>) -> TextField<T> {
+        self.id = Some(id.into());
+        self
+    }
+    /// Value reported with the events of this textfield
+    pub const fn callback(mut self, cb: T) -> TextField<T> {
+        self.callback = cb;
+        self
+    }
+    pub const fn get_callback(&self) -> T {
+        self.callback
+    }
+    pub fn get_label(&self) -> &str {
+        &self.label
+    }
+    pub fn set_label(&mut self, s: impl Into<Cow<'static, str>>) {
+        self.label = s.into();
     }
-    pub const fn password(mut self) -> TextField {
+    pub const fn password(mut self) -> TextField<T> {
         self.password = true;
         self
     }
-    pub const fn label(mut self, s: &'static str) -> TextField {
-        self.label = s;
+    pub fn label(mut self, s: impl Into<Cow<'static, str>>) -> TextField<T> {
+        self.label = s.into();
         self
     }
-    pub const fn writeable(mut self) -> TextField {
+    pub const fn writeable(mut self) -> TextField<T> {
         self.clickable = true;
         self
     }
-    pub const fn font_size(mut self, size: u16) -> TextField {
+    pub const fn font_size(mut self, size: u16) -> TextField<T> {
         self.font_size = size;
         self
     }
-    pub const fn align(mut self, align: TextAlign) -> TextField {
+    /// Overrides the font of the panel for this widget
+    pub fn font(mut self, font: impl Into<FontSource>) -> TextField<T> {
+        self.font = Some(font.into());
+        self
+    }
+    pub const fn align(mut self, align: TextAlign) -> TextField<T> {
         self.text_align = align;
         self
     }
-    pub const fn transparent(mut self) -> TextField {
+    pub const fn transparent(mut self) -> TextField<T> {
         self.transparent = true;
         self
     }
@@ -91,7 +140,7 @@ impl TextField {
     pub const fn is_writeable(&self) -> bool {
         self.clickable
     }
-    pub fn content(mut self, s: &str) -> TextField {
+    pub fn content(mut self, s: &str) -> TextField<T> {
         self.content = s.to_string();
         self
     }
@@ -118,104 +167,79 @@ impl TextField {
     }    
 }
 
-impl std::fmt::Display for TextField {
+impl<T> std::fmt::Display for TextField<T>
+where
+    T: Copy,
+{
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.content)
     }
 }
 
-impl Render for TextField {
-    fn render(&self, canvas: &mut Canvas<Window>) -> Result<(), String> {
+impl<T> Render for TextField<T>
+where
+    T: Copy,
+{
+    fn render(&self, canvas: &mut Canvas<Window>) -> Result<(), GuiError> {
         if !self.transparent {
             canvas.set_draw_color(Color::RGB(200, 200, 200));
             canvas.fill_rect(self.rect)?;
         }
-        if unsafe { crate::DEBUG } {
-            canvas.set_draw_color(Color::RGB(255, 0, 0));
-            canvas.draw_rect(self.bounds())?;
-            canvas.set_draw_color(Color::RGB(0, 255, 0));
-            canvas.draw_rect(self.visual_bounds())?;
-        }
 
         Ok(())
     }
 
     fn render_text(
         &self,
-        ttf: &Sdl2TtfContext,
+        fonts: &FontManager,
         canvas: &mut Canvas<Window>,
-        font_path: &'static str,
-    ) -> Result<(), String> {
-        let texture_creator = canvas.texture_creator();
+        font: &FontSource,
+    ) -> Result<(), GuiError> {
+        let font = self.font.as_ref().unwrap_or(font);
         let secret_text = "*".repeat(self.content.len());
-        if !self.content.is_empty() {
-            let mut font = ttf.load_font(font_path, self.font_size)?;
-            font.set_style(sdl2::ttf::FontStyle::NORMAL);
-            canvas.set_clip_rect(Some(self.rect));
-            let surface = font
-                .render(match self.password {
-                    true => &secret_text,
-                    false => &self.content,
-                })
-                .blended(Color::RGB(0, 0, 0))
-                .map_err(|e| e.to_string())?;
-            let content_tex = texture_creator
-                .create_texture_from_surface(&surface)
-                .map_err(|e| e.to_string())?;
-            let TextureQuery { width, height, .. } = content_tex.query();
-            canvas.copy(
-                &content_tex,
-                None,
-                match self.text_align {
-                    TextAlign::Left(n) => rect!(
-                        self.rect.x + n, 
-                        self.rect.y + self.rect.h / 2 - height as i32 / 2,
-                        width, 
-                        height
-                    ),
-                    TextAlign::Right(n) => rect!(
-                        self.rect.x + self.rect.w - width as i32 - n,
-                        self.rect.y + self.rect.h / 2 - height as i32 / 2,
-                        width,
-                        height
-                    ),
-                    TextAlign::Center => rect!(
-                        self.rect.x + self.rect.w / 2 - width as i32 / 2,
-                        self.rect.y + self.rect.h / 2 - height as i32 / 2,
-                        width,
-                        height
-                    ),
-                }
-            )?;
-            canvas.set_clip_rect(None);
-        }
-        // Label
-        if !self.label.is_empty() && !self.transparent {
-            let mut font = ttf.load_font(font_path, 12)?;
-            font.set_style(sdl2::ttf::FontStyle::NORMAL);
-
-            let surface = font
-                .render(&self.label)
-                .blended(Color::RGB(200, 200, 200))
-                .map_err(|e| e.to_string())?;
-            let label_tex = texture_creator
-                .create_texture_from_surface(&surface)
-                .map_err(|e| e.to_string())?;
-
-
-            let TextureQuery { width, height, .. } = label_tex.query();
-            canvas.copy(
-                &label_tex,
-                None,
-                rect!(
-                    self.rect.x, 
-                    self.rect.y.saturating_sub(height as i32),
+        // Clip to the field within the clip of a scrolled panel, and skip text scrolled out of view
+        let clip = canvas.clip_rect();
+        let visible = clip.map_or(Some(self.rect), |clip| clip.intersection(self.rect));
+        if let (false, Some(visible)) = (self.content.is_empty(), visible) {
+            canvas.set_clip_rect(Some(visible));
+            let style = TextStyle { font, size: self.font_size, color: Color::RGB(0, 0, 0) };
+            let content = match self.password {
+                true => &secret_text,
+                false => &self.content,
+            };
+            self.text_cache.draw(0, canvas, fonts, content, style, |width, height| match self.text_align {
+                TextAlign::Left(n) => rect!(
+                    self.rect.x + n, 
+                    self.rect.y + self.rect.h / 2 - height as i32 / 2,
                     width, 
                     height
                 ),
-            )?;
+                TextAlign::Right(n) => rect!(
+                    self.rect.x + self.rect.w - width as i32 - n,
+                    self.rect.y + self.rect.h / 2 - height as i32 / 2,
+                    width,
+                    height
+                ),
+                TextAlign::Center => rect!(
+                    self.rect.x + self.rect.w / 2 - width as i32 / 2,
+                    self.rect.y + self.rect.h / 2 - height as i32 / 2,
+                    width,
+                    height
+                ),
+            })?;
+            canvas.set_clip_rect(clip);
+        }
+        // Label
+        if !self.transparent {
+            let style = TextStyle { font, size: 12, color: Color::RGB(200, 200, 200) };
+            self.text_cache.draw(1, canvas, fonts, &self.label, style, |width, height| rect!(
+                self.rect.x, 
+                self.rect.y.saturating_sub(height as i32),
+                width, 
+                height
+            ))?;
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+}
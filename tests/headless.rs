@@ -0,0 +1,90 @@
+use guilibrs::widgets::Button;
+use guilibrs::{GuiEvent, Panel, WidgetId, GUI};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+enum Action {
+    #[default]
+    Cancel,
+    Save,
+}
+
+#[test]
+fn simulate_click_reports_the_button_payload() {
+    let panel = Panel::new(
+        "main", (0, 0),
+        vec![
+            Button::new(10, 10, 100, 40).callback(Action::Save),
+            Button::new(10, 60, 100, 40).callback(Action::Cancel),
+        ],
+        vec![], vec![], vec![]
+    );
+    let mut gui: GUI<Action> = GUI::new()
+        .panels(&[panel])
+        .headless()
+        .build()
+        .unwrap();
+
+    match gui.simulate_click(60, 30) {
+        GuiEvent::ButtonPress(path, id, action) => {
+            assert_eq!(path, "main");
+            assert_eq!(id, WidgetId::Index(0));
+            assert_eq!(action, Action::Save);
+        },
+        _ => panic!("expected a button press"),
+    }
+}
+
+fn button_panel(name: &'static str, x: i32, y: i32, action: Action) -> Panel<Action> {
+    Panel::new(name, (0, 0), vec![Button::new(x, y, 100, 40).callback(action)], vec![], vec![], vec![])
+}
+
+fn assert_pressed(event: GuiEvent<Action>, panel: &str, expected: Action) {
+    match event {
+        GuiEvent::ButtonPress(path, _, action) => {
+            assert_eq!(path, panel);
+            assert_eq!(action, expected);
+        },
+        _ => panic!("expected a button press in {panel}"),
+    }
+}
+
+#[test]
+fn modal_panel_captures_all_clicks() {
+    let mut gui: GUI<Action> = GUI::new()
+        .panels(&[
+            button_panel("main", 10, 10, Action::Cancel),
+            button_panel("dialog", 200, 200, Action::Save),
+        ])
+        .headless()
+        .build()
+        .unwrap();
+
+    gui.show_modal("dialog").unwrap();
+    assert!(matches!(gui.simulate_click(60, 30), GuiEvent::None));
+    assert_pressed(gui.simulate_click(250, 220), "dialog", Action::Save);
+
+    gui.close_modal();
+    assert_eq!(gui.modal(), None);
+    assert_pressed(gui.simulate_click(60, 30), "main", Action::Cancel);
+}
+
+#[test]
+fn clicks_go_to_the_topmost_panel() {
+    let mut gui: GUI<Action> = GUI::new()
+        .panels(&[
+            button_panel("bottom", 10, 10, Action::Cancel),
+            button_panel("top", 10, 10, Action::Save),
+        ])
+        .headless()
+        .build()
+        .unwrap();
+
+    assert_pressed(gui.simulate_click(60, 30), "top", Action::Save);
+
+    gui.raise_panel("bottom").unwrap();
+    assert_eq!(gui.panel_order(), ["top", "bottom"]);
+    assert_pressed(gui.simulate_click(60, 30), "bottom", Action::Cancel);
+
+    gui.hide_panel("bottom").unwrap();
+    assert_pressed(gui.simulate_click(60, 30), "top", Action::Save);
+}